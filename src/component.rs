@@ -0,0 +1,132 @@
+use crate::core;
+use crate::errors::MessageError;
+use rbot_messages::messages as msg;
+
+/// A robot component, offering the per-component functions as methods.
+///
+/// This is a thin wrapper around the component functions in `rbot::core`, such
+/// as `use_component` and `await_component`, which stay available. It avoids
+/// passing the component identifier to every call when working with several
+/// components.
+///
+/// # Examples
+///
+/// ```
+/// use rbot::component::Component;
+///
+/// let rifle = Component::new(0);
+/// let radar_msg = rbot::modules::radar()?;
+/// let angle = rbot::conversions::xy_to_angle(radar_msg.x, radar_msg.y);
+/// rifle.await_aim(angle, 0.5)?;
+/// rifle.await_ready()?;
+/// rifle.fire(false)?;
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Component {
+    pub id: i32,
+}
+
+impl Component {
+    /// Creates a handle for the component with the given identifier.
+    pub fn new(id: i32) -> Self {
+        Self { id }
+    }
+
+    /// Fires the component, see `rbot::use_component`.
+    pub fn fire(&self, sticky: bool) -> Result<(), MessageError> {
+        core::use_component(self.id, sticky)
+    }
+
+    /// Waits until the component is ready to be used, see `rbot::await_component`.
+    pub fn await_ready(&self) -> Result<(), MessageError> {
+        core::await_component(self.id)
+    }
+
+    /// Retrieves the status of the component, see `rbot::component_state`.
+    pub fn state(&self) -> Result<msg::RMsgComponentStatus, MessageError> {
+        core::component_state(self.id)
+    }
+
+    /// Rotates the robot so the component faces `angle`, see `rbot::aim`.
+    pub fn aim(&self, angle: f32) -> Result<(), MessageError> {
+        core::aim(self.id, angle)
+    }
+
+    /// Aims the component and waits until it faces `angle`, see `rbot::await_aim`.
+    pub fn await_aim(&self, angle: f32, slack: f32) -> Result<(), MessageError> {
+        core::await_aim(self.id, angle, slack)
+    }
+
+    /// Checks whether the component's cooldown is over.
+    pub fn is_ready(&self) -> Result<bool, MessageError> {
+        Ok(self.state()?.cooldown <= 0.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hostfn::mock;
+    use crate::rotations;
+    use rbot_messages::MessageType;
+
+    /// Answers as a robot that turns instantly to each rotation sent, with
+    /// components that cool down for `cooldown` seconds after each use.
+    fn robot(cooldown: f32) {
+        let (mut angle, mut ready_at) = (0.0, 0.0);
+        mock::on_message(move |name, json| match name {
+            "MsgAngle" => {
+                angle = json["angle"].as_f64().unwrap() as f32;
+                MessageType::Empty(msg::MsgEmpty { value: 0 })
+            }
+            "MsgUse" => {
+                ready_at = mock::clock() + cooldown;
+                MessageType::Empty(msg::MsgEmpty { value: 0 })
+            }
+            "MsgState" => MessageType::RState(msg::RMsgState {
+                angle,
+                health: 100.0,
+                buffs: Vec::new(),
+            }),
+            "MsgComponentStatusQuery" => MessageType::RComponentStatus(msg::RMsgComponentStatus {
+                health: 100.0,
+                cooldown: (ready_at - mock::clock()).max(0.0),
+                is_activated: false,
+            }),
+            "MsgTime" => MessageType::RTime(msg::RMsgTime {
+                timestamp: mock::clock(),
+            }),
+            _ => MessageType::Empty(msg::MsgEmpty { value: 0 }),
+        });
+    }
+
+    fn fired_ids() -> Vec<i64> {
+        mock::sent()
+            .into_iter()
+            .filter(|(name, _)| name == "MsgUse")
+            .map(|(_, json)| json["component_id"].as_i64().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn aims_then_fires_and_waits_for_the_cooldown() {
+        robot(0.5);
+        let rifle = Component::new(1);
+
+        rifle.await_aim(45.0, 0.5).unwrap();
+        let facing =
+            rotations::transform_rotation_from_component(rifle.id, core::state().unwrap().angle);
+        assert!(rotations::angle_distance(facing, 45.0) <= 0.5, "{facing}");
+
+        assert!(rifle.is_ready().unwrap());
+        rifle.fire(false).unwrap();
+        assert!(!rifle.is_ready().unwrap());
+        assert_eq!(rifle.state().unwrap().cooldown, 0.5);
+
+        rifle.await_ready().unwrap();
+        assert!(mock::clock() >= 0.5);
+        assert!(rifle.is_ready().unwrap());
+        rifle.fire(true).unwrap();
+        assert_eq!(fired_ids(), [1, 1]);
+    }
+}
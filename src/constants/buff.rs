@@ -0,0 +1,38 @@
+//! Helpers for the buffs reported in the `buffs` field of scan, laser and
+//! state messages.
+//!
+//! The game server does not publish a list of buff names, so there are no
+//! constants here; compare against names observed in its messages instead.
+
+/// Checks whether a list of buffs contains the buff `name`, ignoring case.
+///
+/// # Examples
+///
+/// ```
+/// use rbot::constants::buff;
+///
+/// // Find the components that share a buff with the robot.
+/// let state = rbot::state()?;
+/// let scan_msg = rbot::modules::scan()?;
+/// let shared = scan_msg
+///     .objects
+///     .iter()
+///     .filter(|o| state.buffs.iter().any(|name| buff::has_buff(&o.buffs, name)));
+/// ```
+pub fn has_buff(buffs: &[String], name: &str) -> bool {
+    buffs.iter().any(|buff| buff.eq_ignore_ascii_case(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn has_buff_ignores_case() {
+        let buffs = ["Alpha".to_string(), "beta".to_string()];
+        assert!(has_buff(&buffs, "alpha"));
+        assert!(has_buff(&buffs, "BETA"));
+        assert!(!has_buff(&buffs, "gamma"));
+        assert!(!has_buff(&[], "alpha"));
+    }
+}
@@ -0,0 +1,66 @@
+//! Arena bounds. Positions are measured from the center of the map, as
+//! reported by `rbot::modules::gps`.
+//!
+//! The game server does not report the arena size, and it may differ between
+//! maps, so the bounds are provided by the caller, e.g. for the map the robot
+//! is written for.
+
+/// The size of a rectangular arena centered on the center of the map.
+///
+/// # Examples
+///
+/// ```
+/// use rbot::constants::map::ArenaBounds;
+///
+/// let arena = ArenaBounds::new(40.0, 25.0);
+/// let gps = rbot::modules::gps()?;
+/// if !arena.in_bounds(gps.x, gps.y, 5.0) {
+///     rbot::velocity(-gps.x, -gps.y, 1.0)?;
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ArenaBounds {
+    /// Half of the arena width, from the center to the left or right edge.
+    pub half_width: f32,
+    /// Half of the arena height, from the center to the top or bottom edge.
+    pub half_height: f32,
+}
+
+impl ArenaBounds {
+    /// Creates the bounds of an arena extending `half_width` units to the left
+    /// and right of the center and `half_height` units above and below it.
+    pub fn new(half_width: f32, half_height: f32) -> Self {
+        Self {
+            half_width,
+            half_height,
+        }
+    }
+
+    /// Checks whether the absolute position (`x`, `y`) is inside the arena,
+    /// keeping at least `margin` units away from the edges.
+    pub fn in_bounds(&self, x: f32, y: f32, margin: f32) -> bool {
+        x.abs() <= self.half_width - margin && y.abs() <= self.half_height - margin
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn in_bounds_includes_the_edges() {
+        let arena = ArenaBounds::new(40.0, 25.0);
+        assert!(arena.in_bounds(0.0, 0.0, 0.0));
+        assert!(arena.in_bounds(40.0, -25.0, 0.0));
+        assert!(!arena.in_bounds(40.1, 0.0, 0.0));
+        assert!(!arena.in_bounds(0.0, -25.1, 0.0));
+    }
+
+    #[test]
+    fn in_bounds_keeps_the_margin() {
+        let arena = ArenaBounds::new(40.0, 25.0);
+        assert!(arena.in_bounds(-35.0, 20.0, 5.0));
+        assert!(!arena.in_bounds(-35.5, 0.0, 5.0));
+        assert!(!arena.in_bounds(0.0, 20.5, 5.0));
+    }
+}
@@ -1,2 +1,4 @@
+pub mod buff;
 pub mod kind;
+pub mod map;
 pub mod tag;
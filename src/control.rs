@@ -0,0 +1,364 @@
+use crate::conversions;
+use crate::conversions::Vec2;
+use crate::core;
+use crate::errors::MessageError;
+use crate::rotations;
+
+/// Keeps the robot's body at a desired heading, correcting drift.
+///
+/// Knockback from thrusters or collisions can turn the robot away from the
+/// heading it was aimed at. `HeadingHold` remembers the desired heading and,
+/// when `maintain` is called each tick, issues a corrective `rotate` only when
+/// the error is larger than `deadband`. This avoids sending many tiny rotations,
+/// which slows down the robot's rotation.
+///
+/// # Examples
+///
+/// ```
+/// let mut hold = rbot::control::HeadingHold::new(90.0, 2.0);
+/// loop {
+///     rbot::modules::await_module(rbot::modules::Module::Thruster)?;
+///     rbot::modules::thrust(0.0)?;
+///     hold.maintain()?;
+/// }
+/// ```
+pub struct HeadingHold {
+    /// The desired body heading in degrees.
+    pub heading: f32,
+    /// The allowed error in degrees before a correction is issued.
+    pub deadband: f32,
+}
+
+impl HeadingHold {
+    /// Creates a new `HeadingHold` for the given `heading` and `deadband` in degrees.
+    pub fn new(heading: f32, deadband: f32) -> Self {
+        Self { heading, deadband }
+    }
+
+    /// Rotates the robot back to the desired heading if it has drifted further
+    /// than the deadband.
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(true)` if a corrective rotation was sent, `Ok(false)` if the
+    /// robot was already within the deadband, or an error of type
+    /// `MessageError` if there was a problem during communication with the
+    /// game server.
+    pub fn maintain(&mut self) -> Result<bool, MessageError> {
+        let current_rotation = core::state()?.angle;
+        if rotations::angle_distance(self.heading, current_rotation) <= self.deadband {
+            return Ok(false);
+        }
+        core::rotate(self.heading)?;
+        Ok(true)
+    }
+}
+
+/// Staggers fire between several components for a steady rate of fire.
+///
+/// Firing every ready component at once fires in volleys with long pauses in
+/// between. `FireScheduler` instead picks at most one ready component per call
+/// to `next`, taking turns in round-robin order, so the components' cooldowns
+/// drift apart and shots are spread out evenly.
+///
+/// # Examples
+///
+/// ```
+/// let mut scheduler = rbot::control::FireScheduler::new(&[0, 1, 2]);
+/// loop {
+///     if let Some(component_id) = scheduler.next()? {
+///         rbot::use_component(component_id, false)?;
+///     }
+///     rbot::sleep(rbot::poll_interval());
+/// }
+/// ```
+pub struct FireScheduler {
+    components: Vec<i32>,
+    turn: usize,
+}
+
+impl FireScheduler {
+    /// Creates a scheduler for the components with the given identifiers.
+    pub fn new(components: &[i32]) -> Self {
+        Self {
+            components: components.to_vec(),
+            turn: 0,
+        }
+    }
+
+    /// Picks the component to fire this tick.
+    ///
+    /// The components are checked in round-robin order, starting after the
+    /// one picked last, and the first one whose cooldown is over is picked.
+    ///
+    /// # Returns
+    ///
+    /// The identifier of the component to fire, `None` if no component is
+    /// ready, or an error of type `MessageError` if there was a problem during
+    /// communication with the game server.
+    // Not an `Iterator`: each call queries the game server and may fail.
+    #[allow(clippy::should_implement_trait)]
+    pub fn next(&mut self) -> Result<Option<i32>, MessageError> {
+        let count = self.components.len();
+        for offset in 0..count {
+            let index = (self.turn + offset) % count;
+            let component_id = self.components[index];
+            if core::component_state(component_id)?.cooldown <= 0.0 {
+                self.turn = (index + 1) % count;
+                return Ok(Some(component_id));
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// Eases the robot's direction of travel instead of snapping to a new one.
+///
+/// Reversing from full speed in one direction to full speed in the opposite
+/// direction wastes momentum. `VelocitySmoother` remembers the last commanded
+/// direction and turns it towards the target direction by at most
+/// `max_turn_rate` degrees per call to `command`, so a reversal is spread over
+/// several ticks.
+///
+/// # Examples
+///
+/// ```
+/// use rbot::conversions::Vec2;
+///
+/// let mut smoother = rbot::control::VelocitySmoother::new(30.0);
+/// loop {
+///     let radar_msg = rbot::modules::radar()?;
+///     smoother.command(Vec2::new(radar_msg.x, radar_msg.y), 1.0)?;
+///     rbot::sleep(0.1);
+/// }
+/// ```
+pub struct VelocitySmoother {
+    /// The largest change of direction in degrees per call to `command`.
+    pub max_turn_rate: f32,
+    direction: Option<f32>,
+}
+
+impl VelocitySmoother {
+    /// Creates a new `VelocitySmoother` turning by at most `max_turn_rate` degrees per call.
+    pub fn new(max_turn_rate: f32) -> Self {
+        Self {
+            max_turn_rate,
+            direction: None,
+        }
+    }
+
+    /// Turns the last commanded direction towards `target_dir` and sets the velocity.
+    ///
+    /// The first call uses `target_dir` as is. A zero `target_dir` keeps the
+    /// last direction, or is passed on unchanged if there is none yet.
+    ///
+    /// # Arguments
+    ///
+    /// * `target_dir` - The desired direction of movement.
+    /// * `speed` - The speed of traversal (between 0 and 1).
+    ///
+    /// # Returns
+    ///
+    /// Returns `Ok(())` if the velocity command was sent successfully, or an
+    /// error of type `MessageError` if there was a problem during
+    /// communication with the game server.
+    pub fn command(&mut self, target_dir: Vec2, speed: f32) -> Result<(), MessageError> {
+        let target = conversions::try_xy_to_angle(target_dir.x, target_dir.y);
+        let direction = match (self.direction, target) {
+            (Some(current), Some(target)) => {
+                let error = rotations::normalize_angle(target - current + 180.0) - 180.0;
+                current + error.clamp(-self.max_turn_rate, self.max_turn_rate)
+            }
+            (None, Some(target)) => target,
+            (Some(current), None) => current,
+            (None, None) => return core::velocity_vec(target_dir, speed),
+        };
+        let direction = rotations::normalize_angle(direction);
+        self.direction = Some(direction);
+        core::velocity_vec(Vec2::from_angle(direction), speed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hostfn::mock;
+    use rbot_messages::messages as msg;
+    use rbot_messages::MessageType;
+
+    fn facing(angle: f32) {
+        mock::on_message(move |name, _| match name {
+            "MsgState" => MessageType::RState(msg::RMsgState {
+                angle,
+                health: 100.0,
+                buffs: Vec::new(),
+            }),
+            _ => MessageType::Empty(msg::MsgEmpty { value: 0 }),
+        });
+    }
+
+    #[test]
+    fn heading_hold_ignores_errors_within_the_deadband() {
+        for angle in [90.0, 88.5, 92.0] {
+            facing(angle);
+            let mut hold = HeadingHold::new(90.0, 2.0);
+            assert_eq!(hold.maintain(), Ok(false));
+            assert_eq!(mock::sent_names(), ["MsgState"]);
+        }
+    }
+
+    #[test]
+    fn heading_hold_corrects_errors_beyond_the_deadband() {
+        facing(355.0);
+        let mut hold = HeadingHold::new(10.0, 2.0);
+        assert_eq!(hold.maintain(), Ok(true));
+        let sent = mock::sent();
+        assert_eq!(sent[1].0, "MsgAngle");
+        assert_eq!(sent[1].1["angle"], 10.0);
+    }
+
+    /// Answers component status queries with the cooldowns in `cooldowns`,
+    /// indexed by component id.
+    fn cooling(cooldowns: Vec<f32>) {
+        mock::on_message(move |name, json| match name {
+            "MsgComponentStatusQuery" => {
+                let id = json["component_id"].as_i64().unwrap() as usize;
+                MessageType::RComponentStatus(msg::RMsgComponentStatus {
+                    health: 100.0,
+                    cooldown: cooldowns[id],
+                    is_activated: false,
+                })
+            }
+            _ => MessageType::Empty(msg::MsgEmpty { value: 0 }),
+        });
+    }
+
+    #[test]
+    fn fire_scheduler_takes_turns_between_ready_components() {
+        cooling(vec![0.0, 0.0, 0.0]);
+        let mut scheduler = FireScheduler::new(&[0, 1, 2]);
+        let picks: Vec<_> = (0..4).map(|_| scheduler.next().unwrap()).collect();
+        assert_eq!(picks, [Some(0), Some(1), Some(2), Some(0)]);
+    }
+
+    #[test]
+    fn fire_scheduler_skips_cooling_components() {
+        cooling(vec![0.0, 1.0, 0.0]);
+        let mut scheduler = FireScheduler::new(&[0, 1, 2]);
+        let picks: Vec<_> = (0..3).map(|_| scheduler.next().unwrap()).collect();
+        assert_eq!(picks, [Some(0), Some(2), Some(0)]);
+
+        cooling(vec![1.0, 1.0]);
+        let mut scheduler = FireScheduler::new(&[0, 1]);
+        assert_eq!(scheduler.next(), Ok(None));
+        assert_eq!(FireScheduler::new(&[]).next(), Ok(None));
+    }
+
+    /// The directions of the velocities sent, in degrees.
+    fn directions() -> Vec<f32> {
+        mock::sent()
+            .into_iter()
+            .filter(|(name, _)| name == "MsgVelocity")
+            .map(|(_, json)| {
+                let x = json["x"].as_f64().unwrap() as f32;
+                let y = json["y"].as_f64().unwrap() as f32;
+                conversions::xy_to_angle(x, y)
+            })
+            .collect()
+    }
+
+    fn assert_angle(actual: f32, expected: f32) {
+        assert!(
+            rotations::angle_distance(actual, expected) < 1e-3,
+            "{actual} != {expected}"
+        );
+    }
+
+    #[test]
+    fn velocity_smoother_limits_the_turn_per_call() {
+        facing(0.0);
+        let mut smoother = VelocitySmoother::new(30.0);
+        smoother.command(Vec2::new(1.0, 0.0), 1.0).unwrap();
+        smoother.command(Vec2::new(0.0, 1.0), 1.0).unwrap();
+        smoother.command(Vec2::new(0.0, 1.0), 1.0).unwrap();
+        smoother.command(Vec2::new(0.0, 1.0), 1.0).unwrap();
+        let directions = directions();
+        for (actual, expected) in directions.into_iter().zip([0.0, 30.0, 60.0, 90.0]) {
+            assert_angle(actual, expected);
+        }
+    }
+
+    #[test]
+    fn velocity_smoother_keeps_the_direction_for_a_zero_target() {
+        facing(0.0);
+        let mut smoother = VelocitySmoother::new(30.0);
+        smoother.command(Vec2::new(0.0, 0.0), 0.0).unwrap();
+        smoother.command(Vec2::new(0.0, -1.0), 1.0).unwrap();
+        smoother.command(Vec2::new(0.0, 0.0), 1.0).unwrap();
+        let sent = mock::sent();
+        assert_eq!(sent[0].1["x"], 0.0);
+        assert_eq!(sent[0].1["y"], 0.0);
+        let directions = directions();
+        assert_angle(directions[1], 270.0);
+        assert_angle(directions[2], 270.0);
+    }
+
+    /// Answers as components that cool down for 1 second after each use, on a
+    /// clock advanced by `rbot::sleep`. Component `i` is ready at `ready_at[i]`.
+    fn firing(mut ready_at: Vec<f32>) {
+        mock::on_message(move |name, json| {
+            let id = json["component_id"].as_i64().unwrap_or(0) as usize;
+            match name {
+                "MsgUse" => {
+                    ready_at[id] = mock::clock() + 1.0;
+                    MessageType::Empty(msg::MsgEmpty { value: 0 })
+                }
+                "MsgComponentStatusQuery" => {
+                    MessageType::RComponentStatus(msg::RMsgComponentStatus {
+                        health: 100.0,
+                        cooldown: (ready_at[id] - mock::clock()).max(0.0),
+                        is_activated: false,
+                    })
+                }
+                _ => MessageType::Empty(msg::MsgEmpty { value: 0 }),
+            }
+        });
+    }
+
+    #[test]
+    fn fire_scheduler_staggers_components_with_offset_cooldowns() {
+        firing(vec![0.0, 0.5]);
+        let mut scheduler = FireScheduler::new(&[0, 1]);
+        let mut shots = Vec::new();
+        for _ in 0..12 {
+            if let Some(component_id) = scheduler.next().unwrap() {
+                core::use_component(component_id, false).unwrap();
+                shots.push((component_id, mock::clock()));
+            }
+            core::sleep(0.25);
+        }
+        assert_eq!(
+            shots,
+            [(0, 0.0), (1, 0.5), (0, 1.0), (1, 1.5), (0, 2.0), (1, 2.5)]
+        );
+    }
+
+    #[test]
+    fn velocity_smoother_spreads_a_reversal_over_several_calls() {
+        facing(0.0);
+        let mut smoother = VelocitySmoother::new(45.0);
+        smoother.command(Vec2::new(1.0, 0.0), 1.0).unwrap();
+        for _ in 0..5 {
+            smoother.command(Vec2::new(-1.0, 0.0), 1.0).unwrap();
+        }
+        let directions = directions();
+        assert_eq!(directions.len(), 6);
+        for pair in directions.windows(2) {
+            assert!(rotations::angle_distance(pair[0], pair[1]) <= 45.0 + 1e-3);
+        }
+        // Four turns of 45° to reverse, then it holds the new direction.
+        assert!(rotations::angle_distance(directions[3], 180.0) > 1.0);
+        assert_angle(directions[4], 180.0);
+        assert_angle(directions[5], 180.0);
+    }
+}
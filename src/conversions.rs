@@ -1,3 +1,4 @@
+use crate::rotations;
 use std::f32::consts::PI;
 
 /// Computes the angle (in degrees) from the positive x-axis to a point (x, y) in the Cartesian plane.
@@ -19,12 +20,73 @@ use std::f32::consts::PI;
 /// # Examples
 ///
 /// ```
+/// use rbot::conversions::xy_to_angle;
+///
 /// let x = 1.0;
 /// let y = 1.0;
 /// let angle = xy_to_angle(x, y);
+/// assert_eq!(angle, 45.0);
 /// ```
 pub fn xy_to_angle(x: f32, y: f32) -> f32 {
-    f32::atan2(y, x) * 180. / PI
+    y.atan2(x) * 180. / PI
+}
+
+/// Computes the angle (in degrees) from the positive x-axis to a point (x, y), in the range [0, 360).
+///
+/// This function behaves like `xy_to_angle`, but returns the angle normalized
+/// to [0, 360) instead of (-180, 180], matching the angle convention used by
+/// `aim` and `thrust`.
+///
+/// # Arguments
+///
+/// * `x` - The x-coordinate of the point.
+/// * `y` - The y-coordinate of the point.
+///
+/// # Returns
+///
+/// The angle in degrees from the positive x-axis to the point (x, y), in the range [0, 360).
+///
+/// # Examples
+///
+/// ```
+/// use rbot::conversions::xy_to_angle_360;
+///
+/// let angle = xy_to_angle_360(0.0, -1.0);
+/// assert_eq!(angle, 270.0);
+/// ```
+pub fn xy_to_angle_360(x: f32, y: f32) -> f32 {
+    rotations::normalize_angle(xy_to_angle(x, y))
+}
+
+/// Points closer to the origin than this distance have no meaningful direction.
+pub const ORIGIN_EPSILON: f32 = 1e-4;
+
+/// Computes the angle (in degrees) from the positive x-axis to a point (x, y), if it has a direction.
+///
+/// `xy_to_angle(0.0, 0.0)` returns 0 although the origin has no direction. This
+/// happens e.g. when the radar reports an enemy overlapping the robot. This
+/// function returns `None` instead, so callers don't silently aim at 0°.
+///
+/// # Arguments
+///
+/// * `x` - The x-coordinate of the point.
+/// * `y` - The y-coordinate of the point.
+///
+/// # Returns
+///
+/// The angle in degrees in the range (-180, 180] like `xy_to_angle`, or `None`
+/// if the point is within `ORIGIN_EPSILON` of the origin.
+///
+/// # Examples
+///
+/// ```
+/// use rbot::conversions::try_xy_to_angle;
+///
+/// assert_eq!(try_xy_to_angle(0.0, 0.0), None);
+/// assert_eq!(try_xy_to_angle(0.0, 1.0), Some(90.0));
+/// ```
+pub fn try_xy_to_angle(x: f32, y: f32) -> Option<f32> {
+    (x.hypot(y) >= ORIGIN_EPSILON).then(|| xy_to_angle(x, y))
 }
 
 /// Computes the Cartesian coordinates (x, y) corresponding to a given angle (in degrees) from the positive x-axis.
@@ -46,10 +108,348 @@ pub fn xy_to_angle(x: f32, y: f32) -> f32 {
 /// # Examples
 ///
 /// ```
-/// let angle = 45.0;
+/// use rbot::conversions::angle_to_xy;
+///
+/// let angle = 90.0;
 /// let [x, y] = angle_to_xy(angle);
+/// assert!(x.abs() < 1e-6 && (y - 1.0).abs() < 1e-6);
 /// ```
 pub fn angle_to_xy(angle: f32) -> [f32; 2] {
     let rad = angle * PI / 180.;
     [rad.cos(), rad.sin()]
 }
+
+/// Converts a point (x, y) to polar coordinates.
+///
+/// This is the natural form of radar data: the distance and the bearing of a
+/// contact. `from_polar` converts back.
+///
+/// # Arguments
+///
+/// * `x` - The x-coordinate of the point.
+/// * `y` - The y-coordinate of the point.
+///
+/// # Returns
+///
+/// The tuple `(radius, angle_deg)`, where `radius` is the distance from the
+/// origin and `angle_deg` the angle like `xy_to_angle`, in (-180, 180]. The
+/// origin is converted to `(0.0, 0.0)`.
+///
+/// # Examples
+///
+/// ```
+/// use rbot::conversions::to_polar;
+///
+/// let (radius, angle) = to_polar(0.0, 2.0);
+/// assert_eq!((radius, angle), (2.0, 90.0));
+/// ```
+pub fn to_polar(x: f32, y: f32) -> (f32, f32) {
+    (x.hypot(y), xy_to_angle(x, y))
+}
+
+/// Converts polar coordinates to a point (x, y).
+///
+/// This is the inverse of `to_polar`.
+///
+/// # Arguments
+///
+/// * `radius` - The distance from the origin.
+/// * `angle_deg` - The angle in degrees from the positive x-axis.
+///
+/// # Returns
+///
+/// An array `[x, y]` containing the Cartesian coordinates.
+///
+/// # Examples
+///
+/// ```
+/// use rbot::conversions::from_polar;
+///
+/// let [x, y] = from_polar(2.0, 180.0);
+/// assert!((x + 2.0).abs() < 1e-6 && y.abs() < 1e-6);
+/// ```
+pub fn from_polar(radius: f32, angle_deg: f32) -> [f32; 2] {
+    let [x, y] = angle_to_xy(angle_deg);
+    [x * radius, y * radius]
+}
+
+/// Computes the angle (in degrees) from the positive x-axis to a point (x, y) with `f64` precision.
+///
+/// This is the `f64` variant of `xy_to_angle`. The message types use `f32`, so
+/// prefer `xy_to_angle` in general, and use this variant in code that
+/// accumulates angles over a long time, such as a spiral search, where `f32`
+/// rounding errors add up.
+///
+/// # Examples
+///
+/// ```
+/// use rbot::conversions::xy_to_angle_f64;
+///
+/// let angle = xy_to_angle_f64(1.0, 1.0);
+/// assert!((angle - 45.0).abs() < 1e-12);
+/// ```
+pub fn xy_to_angle_f64(x: f64, y: f64) -> f64 {
+    y.atan2(x) * 180. / core::f64::consts::PI
+}
+
+/// Computes the unit vector `[x, y]` for an angle (in degrees) with `f64` precision.
+///
+/// This is the `f64` variant of `angle_to_xy`, see `xy_to_angle_f64` for when
+/// to prefer it.
+///
+/// # Examples
+///
+/// ```
+/// use rbot::conversions::angle_to_xy_f64;
+///
+/// let [x, y] = angle_to_xy_f64(45.0);
+/// assert!((x - y).abs() < 1e-12);
+/// ```
+pub fn angle_to_xy_f64(angle: f64) -> [f64; 2] {
+    let rad = angle * core::f64::consts::PI / 180.;
+    [rad.cos(), rad.sin()]
+}
+
+/// Reflects a vector off a surface with the given normal.
+///
+/// This function computes `v - 2 * (v · n) * n`, the direction a vector `v`
+/// takes after bouncing off a surface such as a wall. The normal is normalized
+/// internally, so it does not need to be a unit vector.
+///
+/// # Arguments
+///
+/// * `vx` - The x-component of the vector to reflect.
+/// * `vy` - The y-component of the vector to reflect.
+/// * `nx` - The x-component of the surface normal.
+/// * `ny` - The y-component of the surface normal.
+///
+/// # Returns
+///
+/// An array `[x, y]` containing the reflected vector. If the normal has zero
+/// length, the vector is returned unchanged.
+///
+/// # Examples
+///
+/// ```
+/// use rbot::conversions::reflect;
+///
+/// // Bounce a velocity pointing down-right off the floor.
+/// let [x, y] = reflect(1.0, -1.0, 0.0, 1.0);
+/// assert_eq!([x, y], [1.0, 1.0]);
+/// ```
+pub fn reflect(vx: f32, vy: f32, nx: f32, ny: f32) -> [f32; 2] {
+    let length = nx.hypot(ny);
+    if length == 0.0 {
+        return [vx, vy];
+    }
+    let (nx, ny) = (nx / length, ny / length);
+    let dot = vx * nx + vy * ny;
+    [vx - 2.0 * dot * nx, vy - 2.0 * dot * ny]
+}
+
+/// Clamps a value to the unit range [0, 1].
+///
+/// This is useful for speeds computed from a ratio, such as a distance divided
+/// by a maximum distance, which can otherwise leave the range accepted by
+/// `velocity`.
+///
+/// # Arguments
+///
+/// * `v` - The value to clamp.
+///
+/// # Returns
+///
+/// The value clamped between 0 and 1.
+///
+/// # Examples
+///
+/// ```
+/// use rbot::conversions::clamp_unit;
+///
+/// // Slow down within 10 units of the target.
+/// let distance = 4.0;
+/// assert_eq!(clamp_unit(distance / 10.0), 0.4);
+/// let distance = 25.0;
+/// assert_eq!(clamp_unit(distance / 10.0), 1.0);
+/// ```
+pub fn clamp_unit(v: f32) -> f32 {
+    v.clamp(0.0, 1.0)
+}
+
+/// Shortens a vector to a maximum length while preserving its direction.
+///
+/// Vectors that are already shorter than `max` are returned unchanged.
+///
+/// # Arguments
+///
+/// * `x` - The x-component of the vector.
+/// * `y` - The y-component of the vector.
+/// * `max` - The maximum length of the returned vector.
+///
+/// # Returns
+///
+/// An array `[x, y]` containing the vector with a length of at most `max`.
+///
+/// # Examples
+///
+/// ```
+/// use rbot::conversions::clamp_magnitude;
+///
+/// let [x, y] = clamp_magnitude(3.0, 4.0, 2.5);
+/// assert_eq!([x, y], [1.5, 2.0]);
+/// ```
+pub fn clamp_magnitude(x: f32, y: f32, max: f32) -> [f32; 2] {
+    let length = x.hypot(y);
+    if length <= max {
+        return [x, y];
+    }
+    let scale = max / length;
+    [x * scale, y * scale]
+}
+
+/// A two-dimensional vector, e.g. a direction or a position relative to the robot.
+///
+/// # Examples
+///
+/// ```no_run
+/// use rbot::conversions::Vec2;
+///
+/// fn main() -> Result<(), rbot::errors::MessageError> {
+///     let direction = Vec2::from_angle(90.0);
+///     rbot::velocity_vec(direction, 1.0)?;
+///     Ok(())
+/// }
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Vec2 {
+    pub x: f32,
+    pub y: f32,
+}
+
+impl Vec2 {
+    /// Creates a new vector from its components.
+    pub fn new(x: f32, y: f32) -> Self {
+        Self { x, y }
+    }
+
+    /// Creates a unit vector pointing at `angle_deg` degrees from the positive x-axis.
+    pub fn from_angle(angle_deg: f32) -> Self {
+        let [x, y] = angle_to_xy(angle_deg);
+        Self { x, y }
+    }
+
+    /// Creates a vector of length `len` pointing at `angle_deg` degrees from the positive x-axis.
+    pub fn from_angle_scaled(angle_deg: f32, len: f32) -> Self {
+        Self::from_angle(angle_deg) * len
+    }
+
+    /// The angle in degrees from the positive x-axis to the vector, in the range (-180, 180].
+    pub fn angle(&self) -> f32 {
+        xy_to_angle(self.x, self.y)
+    }
+
+    /// The length of the vector.
+    pub fn length(&self) -> f32 {
+        self.x.hypot(self.y)
+    }
+}
+
+impl core::ops::Mul<f32> for Vec2 {
+    type Output = Vec2;
+
+    fn mul(self, rhs: f32) -> Vec2 {
+        Vec2::new(self.x * rhs, self.y * rhs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: [f32; 2], expected: [f32; 2]) {
+        let error = (actual[0] - expected[0]).hypot(actual[1] - expected[1]);
+        assert!(error < 1e-5, "{actual:?} != {expected:?}");
+    }
+
+    #[test]
+    fn reflect_flips_the_normal_component() {
+        assert_close(reflect(1.0, -1.0, 0.0, 1.0), [1.0, 1.0]);
+        assert_close(reflect(2.0, 3.0, -1.0, 0.0), [-2.0, 3.0]);
+    }
+
+    #[test]
+    fn reflect_normalizes_the_normal() {
+        assert_close(reflect(1.0, -1.0, 0.0, 5.0), reflect(1.0, -1.0, 0.0, 1.0));
+        assert_close(reflect(1.0, 0.0, 1.0, 1.0), [0.0, -1.0]);
+    }
+
+    #[test]
+    fn reflect_keeps_parallel_vectors_and_zero_normals() {
+        assert_close(reflect(3.0, 0.0, 0.0, 1.0), [3.0, 0.0]);
+        assert_eq!(reflect(1.0, 2.0, 0.0, 0.0), [1.0, 2.0]);
+    }
+
+    #[test]
+    fn clamp_unit_clamps_to_zero_and_one() {
+        assert_eq!(clamp_unit(-0.5), 0.0);
+        assert_eq!(clamp_unit(0.25), 0.25);
+        assert_eq!(clamp_unit(3.0), 1.0);
+    }
+
+    #[test]
+    fn clamp_magnitude_shortens_long_vectors() {
+        assert_close(clamp_magnitude(3.0, 4.0, 2.5), [1.5, 2.0]);
+        assert_close(clamp_magnitude(-6.0, 8.0, 5.0), [-3.0, 4.0]);
+    }
+
+    #[test]
+    fn clamp_magnitude_keeps_short_vectors() {
+        assert_eq!(clamp_magnitude(3.0, 4.0, 5.0), [3.0, 4.0]);
+        assert_eq!(clamp_magnitude(0.0, 0.0, 1.0), [0.0, 0.0]);
+    }
+
+    #[test]
+    fn f64_variants_match_the_f32_functions() {
+        for [x, y] in [[1.0, 1.0], [-2.0, 0.5], [0.0, -3.0], [-1.0, 0.0]] {
+            let angle = xy_to_angle_f64(x as f64, y as f64);
+            assert!((angle as f32 - xy_to_angle(x, y)).abs() < 1e-4);
+        }
+        for angle in [0.0, 45.0, 135.0, -90.0, 720.0] {
+            let [x, y] = angle_to_xy_f64(angle as f64);
+            assert_close([x as f32, y as f32], angle_to_xy(angle));
+        }
+    }
+
+    #[test]
+    fn f64_variants_round_trip() {
+        let angle = 123.456_789_f64;
+        let [x, y] = angle_to_xy_f64(angle);
+        assert!((xy_to_angle_f64(x, y) - angle).abs() < 1e-9);
+    }
+
+    #[test]
+    fn polar_round_trips() {
+        for [x, y] in [
+            [1.0, 0.0],
+            [3.0, 4.0],
+            [-2.0, 5.0],
+            [-1.5, -1.5],
+            [0.5, -7.0],
+        ] {
+            let (radius, angle) = to_polar(x, y);
+            assert_close(from_polar(radius, angle), [x, y]);
+        }
+        for (radius, angle) in [(2.0, 90.0), (5.0, -135.0), (1.0, 180.0)] {
+            let [x, y] = from_polar(radius, angle);
+            let (r, a) = to_polar(x, y);
+            assert!((r - radius).abs() < 1e-5);
+            assert!(rotations::angle_distance(a, angle) < 1e-3);
+        }
+    }
+
+    #[test]
+    fn polar_zero_vector() {
+        assert_eq!(to_polar(0.0, 0.0), (0.0, 0.0));
+        assert_close(from_polar(0.0, 123.0), [0.0, 0.0]);
+    }
+}
@@ -1,9 +1,37 @@
+use crate::conversions;
 use crate::errors::MessageError;
 use crate::hostfn;
 use crate::rotations;
 use crate::rotations::transform_rotation_to_component;
 use rbot_messages::messages as msg;
 use rbot_messages::MessageType;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+thread_local! {
+    /// The number of components `use_component` checks ids against, if any.
+    static STRICT: std::cell::Cell<Option<usize>> = const { std::cell::Cell::new(None) };
+}
+
+/// Enables or disables strict mode for `use_component`.
+///
+/// In strict mode, `use_component` returns `MessageError::UnknownComponent`
+/// naming the id if it is not between 0 and `component_count` (exclusive),
+/// instead of sending it and getting the opaque `BadCommand` from the game
+/// server. The game server does not report how many components a robot has,
+/// so pass the count of your robot's build, or `None` to disable the check.
+/// Strict mode is disabled by default.
+///
+/// # Examples
+///
+/// ```
+/// // The robot has four components.
+/// rbot::set_strict(Some(4));
+/// // Fails with `MessageError::UnknownComponent(99)`.
+/// let result = rbot::use_component(99, false);
+/// ```
+pub fn set_strict(component_count: Option<usize>) {
+    STRICT.with(|strict| strict.set(component_count));
+}
 
 /// Fires the component with the specified `component_id`.
 ///
@@ -17,7 +45,9 @@ use rbot_messages::MessageType;
 ///
 /// # Returns
 ///
-/// Whether or not the command sent was recived successfully or not.
+/// Whether or not the command sent was recived successfully or not. In strict
+/// mode (see `set_strict`), `MessageError::UnknownComponent` is returned
+/// without sending anything if `component_id` is out of range.
 ///
 /// # Examples
 ///
@@ -32,6 +62,11 @@ use rbot_messages::MessageType;
 /// }
 /// ```
 pub fn use_component(component_id: i32, sticky: bool) -> Result<(), MessageError> {
+    if let Some(count) = STRICT.with(|strict| strict.get()) {
+        if usize::try_from(component_id).map_or(true, |id| id >= count) {
+            return Err(MessageError::UnknownComponent(component_id));
+        }
+    }
     let sticky = match sticky {
         false => 0,
         _ => 1,
@@ -40,7 +75,7 @@ pub fn use_component(component_id: i32, sticky: bool) -> Result<(), MessageError
         component_id,
         sticky,
     };
-    let response = hostfn::send_message(&msg_use);
+    let response = hostfn::send_message(&msg_use)?;
 
     match response {
         MessageType::Error(m) => Err(MessageError::BadCommand(m.error_code)),
@@ -48,6 +83,60 @@ pub fn use_component(component_id: i32, sticky: bool) -> Result<(), MessageError
     }
 }
 
+/// The result of `fire_checked`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FireOutcome {
+    /// Whether the component fired.
+    pub fired: bool,
+    /// The remaining cooldown in seconds until the component can fire again.
+    pub next_ready_in: f32,
+}
+
+/// Fires the component with the specified `component_id` once, reporting whether it fired.
+///
+/// `use_component` returns `Ok(())` even if the component was on cooldown and
+/// did not fire, because the game server accepts the command either way, and
+/// its response does not say whether a shot went off. This function checks
+/// the component's cooldown first and only fires if it is ready. Afterwards it
+/// waits for the next action loop, in which the shot is performed, and reads
+/// the new cooldown, which is useful for ammo and timing management. This
+/// costs three extra messages per call compared to `use_component`.
+///
+/// # Arguments
+///
+/// * `component_id` - The identifier of the component to fire.
+///
+/// # Returns
+///
+/// A `FireOutcome` telling whether the component fired and when it is ready
+/// again, or an error of type `MessageError` if there was a problem during
+/// communication with the game server.
+///
+/// # Examples
+///
+/// ```
+/// let outcome = rbot::fire_checked(0)?;
+/// if !outcome.fired {
+///     rbot::sleep(outcome.next_ready_in);
+/// }
+/// ```
+pub fn fire_checked(component_id: i32) -> Result<FireOutcome, MessageError> {
+    let cooldown = component_state(component_id)?.cooldown;
+    if cooldown > 0.0 {
+        return Ok(FireOutcome {
+            fired: false,
+            next_ready_in: cooldown,
+        });
+    }
+    use_component(component_id, false)?;
+    // The cooldown only starts once the shot is performed in the next action loop.
+    await_action()?;
+    Ok(FireOutcome {
+        fired: true,
+        next_ready_in: component_state(component_id)?.cooldown.max(0.0),
+    })
+}
+
 /// Sets the velocity and direction for the robot's traversal.
 ///
 /// This function specifies the direction and speed at which the robot should
@@ -58,8 +147,8 @@ pub fn use_component(component_id: i32, sticky: bool) -> Result<(), MessageError
 /// The `speed` parameter is a decimal number between 0 and 1, indicating the
 /// magnitude of the velocity vector. A `speed` value of 0 corresponds to no
 /// movement, while a `speed` value of 1 represents maximum speed in the
-/// specified direction. The speed will be clamped between 0 and 1 on the game
-/// server.
+/// specified direction. The speed is clamped between 0 and 1 before it is sent
+/// to the game server.
 ///
 /// # Arguments
 ///
@@ -69,7 +158,9 @@ pub fn use_component(component_id: i32, sticky: bool) -> Result<(), MessageError
 ///
 /// # Returns
 ///
-/// Returns `Ok(())` if the velocity command was sent successfully.
+/// Returns `Ok(())` if the velocity command was sent successfully, or
+/// `MessageError::InvalidArgument` without sending anything if `x`, `y` or
+/// `speed` is NaN or infinite.
 ///
 /// # Examples
 ///
@@ -83,14 +174,130 @@ pub fn use_component(component_id: i32, sticky: bool) -> Result<(), MessageError
 /// }
 /// ```
 pub fn velocity(x: f32, y: f32, speed: f32) -> Result<(), MessageError> {
+    if !x.is_finite() || !y.is_finite() {
+        return Err(MessageError::InvalidArgument(
+            "velocity direction must be finite",
+        ));
+    }
+    if !speed.is_finite() {
+        return Err(MessageError::InvalidArgument(
+            "velocity speed must be finite",
+        ));
+    }
+    let speed = conversions::clamp_unit(speed);
     let msg_use = msg::MsgVelocity { x, y, speed };
-    let response = hostfn::send_message(&msg_use);
+    let response = hostfn::send_message(&msg_use)?;
     match response {
         MessageType::Error(m) => Err(MessageError::BadCommand(m.error_code)),
         _ => Ok(()),
     }
 }
 
+/// Sets the velocity of the robot from a direction vector.
+///
+/// This function behaves like `velocity`, but takes the direction as a
+/// `Vec2`, which composes with the vector helpers in `conversions`.
+///
+/// # Arguments
+///
+/// * `dir` - The direction of movement.
+/// * `speed` - The speed of traversal (between 0 and 1).
+///
+/// # Returns
+///
+/// Returns `Ok(())` if the velocity command was sent successfully.
+///
+/// # Examples
+///
+/// ```
+/// use rbot::conversions::Vec2;
+///
+/// // Move towards the top right.
+/// rbot::velocity_vec(Vec2::from_angle(45.0), 0.8)?;
+/// ```
+pub fn velocity_vec(dir: conversions::Vec2, speed: f32) -> Result<(), MessageError> {
+    velocity(dir.x, dir.y, speed)
+}
+
+/// Stops the robot's movement.
+///
+/// This is a shorthand for setting a velocity with a speed of 0.
+///
+/// # Examples
+///
+/// ```
+/// rbot::velocity(1.0, 0.0, 1.0)?;
+/// rbot::sleep(1.0);
+/// rbot::stop()?;
+/// ```
+pub fn stop() -> Result<(), MessageError> {
+    velocity(0.0, 0.0, 0.0)
+}
+
+/// Moves the robot in a direction for a fixed duration, then stops it.
+///
+/// This function sets the velocity like `velocity`, sleeps for
+/// `duration_secs` and then calls `stop`, so it blocks for the whole duration.
+/// It is useful for scripted maneuvers such as dodging.
+///
+/// # Arguments
+///
+/// * `x` - The X-component of the direction vector.
+/// * `y` - The Y-component of the direction vector.
+/// * `speed` - The speed of traversal (between 0 and 1).
+/// * `duration_secs` - How long to move in seconds.
+///
+/// # Returns
+///
+/// Returns `Ok(())` once the robot has been stopped, or an error of type
+/// `MessageError` if either velocity command fails.
+///
+/// # Examples
+///
+/// ```
+/// // Move to the right for half a second.
+/// rbot::velocity_for(1.0, 0.0, 1.0, 0.5)?;
+/// ```
+pub fn velocity_for(x: f32, y: f32, speed: f32, duration_secs: f32) -> Result<(), MessageError> {
+    velocity(x, y, speed)?;
+    sleep(duration_secs);
+    stop()
+}
+
+/// Stops the robot's movement when dropped.
+///
+/// Without it, a robot keeps drifting at its last velocity after `main`
+/// returns, including early returns and errors propagated with `?`. Hold a
+/// `StopGuard` for as long as the robot should be allowed to move. Since
+/// `drop` cannot return errors, a failure to stop the robot is logged with
+/// `print` instead.
+///
+/// Robots run on wasm32, which aborts on panic, so the guard is not dropped
+/// when the robot panics.
+///
+/// Sticky fire is not affected, since the game server offers no command to
+/// cancel it.
+///
+/// # Examples
+///
+/// ```
+/// fn run() -> Result<(), rbot::errors::MessageError> {
+///     let _guard = rbot::StopGuard;
+///     rbot::velocity(1.0, 0.0, 1.0)?;
+///     // ...
+///     Ok(())
+/// } // The robot stops here, also when `?` returns early.
+/// ```
+pub struct StopGuard;
+
+impl Drop for StopGuard {
+    fn drop(&mut self) {
+        if let Err(e) = stop() {
+            print(&format!("Failed to stop the robot: {e}\n"));
+        }
+    }
+}
+
 /// Rotates the robot to a specified angle in degrees.
 ///
 /// This function rotates the robot to the desired angle measured in degrees.
@@ -118,7 +325,7 @@ pub fn velocity(x: f32, y: f32, speed: f32) -> Result<(), MessageError> {
 /// ```
 pub fn rotate(angle: f32) -> Result<(), MessageError> {
     let msg_use = msg::MsgAngle { angle };
-    let response = hostfn::send_message(&msg_use);
+    let response = hostfn::send_message(&msg_use)?;
     match response {
         MessageType::Error(m) => Err(MessageError::BadCommand(m.error_code)),
         _ => Ok(()),
@@ -154,10 +361,13 @@ pub fn rotate(angle: f32) -> Result<(), MessageError> {
 /// rbot::print(&format!("Component 0 is at angle 90 degrees: {is_at_angle}"));
 /// ```
 pub fn at_rotation(component_id: i32, angle: f32, slack: f32) -> Result<bool, MessageError> {
-    let target_angle = rotations::transform_rotation_to_component(component_id, angle);
+    let target_angle = rotations::transform_rotation_to_component_normalized(component_id, angle);
     let current_rotation = state()?.angle;
-    let angle_difference = rotations::angle_distance(target_angle, current_rotation);
-    Ok(angle_difference < slack)
+    Ok(rotations::angle_within(
+        target_angle,
+        current_rotation,
+        slack,
+    ))
 }
 
 /// Aims a robot component towards a specified angle in a 2D coordinate system.
@@ -200,6 +410,160 @@ pub fn aim(component_id: i32, angle: f32) -> Result<(), MessageError> {
     rotate(transform_rotation_to_component(component_id, angle))
 }
 
+thread_local! {
+    static COMPONENT_OFFSETS: std::cell::RefCell<std::collections::HashMap<i32, [f32; 2]>> =
+        std::cell::RefCell::new(std::collections::HashMap::new());
+}
+
+/// Sets the position of a component relative to the center of the robot.
+///
+/// The game server does not report where components are mounted, so the
+/// offsets used by `aim_at_world_point` have to be configured here. The
+/// offset is given in the robot's own frame, i.e. as it is when the robot's
+/// rotation is 0. Components without a configured offset are assumed to be
+/// at the center.
+///
+/// # Arguments
+///
+/// * `component_id` - The identifier of the component.
+/// * `offset` - The position `[x, y]` of the component relative to the robot's center.
+///
+/// # Examples
+///
+/// ```
+/// // Component 1 is mounted 1.5 units above the center.
+/// rbot::set_component_offset(1, [0.0, 1.5]);
+/// ```
+pub fn set_component_offset(component_id: i32, offset: [f32; 2]) {
+    COMPONENT_OFFSETS.with(|offsets| offsets.borrow_mut().insert(component_id, offset));
+}
+
+/// Returns the position of a component relative to the center of the robot,
+/// as configured with `set_component_offset`, or `[0.0, 0.0]` if none is set.
+pub fn component_offset(component_id: i32) -> [f32; 2] {
+    COMPONENT_OFFSETS.with(|offsets| {
+        offsets
+            .borrow()
+            .get(&component_id)
+            .copied()
+            .unwrap_or([0.0, 0.0])
+    })
+}
+
+/// Aims a robot component at an absolute position on the map, accounting for
+/// where the component is mounted.
+///
+/// A component mounted away from the robot's center sees a target at a
+/// slightly different angle than the center does. This function reads the
+/// robot's position with GPS, subtracts the component's offset (see
+/// `set_component_offset`), rotated to where the robot will face once aimed,
+/// and aims at the resulting angle.
+///
+/// # Arguments
+///
+/// * `component_id` - The identifier of the robot component to aim.
+/// * `world_x` - The x-coordinate of the target from the center of the map.
+/// * `world_y` - The y-coordinate of the target from the center of the map.
+///
+/// # Returns
+///
+/// Returns `Ok(())` if the rotation was sent successfully, or an error of type
+/// `MessageError` if there was a problem during communication with the game
+/// server.
+///
+/// # Examples
+///
+/// ```
+/// rbot::set_component_offset(0, [1.0, 0.0]);
+/// rbot::aim_at_world_point(0, 20.0, -5.0)?;
+/// ```
+pub fn aim_at_world_point(
+    component_id: i32,
+    world_x: f32,
+    world_y: f32,
+) -> Result<(), MessageError> {
+    let gps = crate::modules::gps()?;
+    let (dx, dy) = (world_x - gps.x, world_y - gps.y);
+
+    // The offset turns with the robot, so use the rotation it will have once aimed.
+    let body_angle =
+        transform_rotation_to_component(component_id, conversions::xy_to_angle(dx, dy));
+    let [offset_x, offset_y] = rotations::rotate_point(component_offset(component_id), body_angle);
+    aim(
+        component_id,
+        conversions::xy_to_angle(dx - offset_x, dy - offset_y),
+    )
+}
+
+/// Retrieves the world angle the specified robot component is currently aimed at.
+///
+/// This function is the counterpart of `aim`. It reads the robot's current
+/// rotation and transforms it from the reference frame of `component_id` back
+/// to the global coordinate system, which is useful for logging and display.
+///
+/// # Arguments
+///
+/// * `component_id` - The identifier of the robot component to read the aim of.
+///
+/// # Returns
+///
+/// Returns the world angle in degrees, normalized to [0, 360), or an error of
+/// type `MessageError` if there was a problem during communication with the
+/// game server.
+///
+/// # Examples
+///
+/// ```
+/// rbot::await_aim(1, 45.0, 0.5)?;
+/// let angle = rbot::component_world_aim(1)?;
+/// rbot::print(&format!("Component 1 is aimed at {angle:.1} degrees"));
+/// ```
+pub fn component_world_aim(component_id: i32) -> Result<f32, MessageError> {
+    let current_rotation = state()?.angle;
+    Ok(rotations::transform_rotation_from_component_normalized(
+        component_id,
+        current_rotation,
+    ))
+}
+
+/// Aims a robot component towards a specified angle, unless it is already
+/// aimed close enough.
+///
+/// Calling `aim` every tick with tiny angle changes slows down the robot's
+/// rotation. This function only sends the rotation when the component's current
+/// aim differs from `angle` by more than `deadband` degrees, which makes it
+/// suitable for closed-loop tracking.
+///
+/// # Arguments
+///
+/// * `component_id` - The identifier of the robot component to aim.
+/// * `angle` - The target angle in degrees, where 0 degrees points towards the right.
+/// * `deadband` - The allowed difference in degrees before a rotation is sent.
+///
+/// # Returns
+///
+/// Returns `Ok(true)` if a rotation was sent, `Ok(false)` if the component was
+/// already within the deadband, or an error of type `MessageError` if there was
+/// a problem during communication with the game server.
+///
+/// # Examples
+///
+/// ```
+/// loop {
+///     let radar_msg = rbot::modules::radar()?;
+///     let angle = rbot::conversions::xy_to_angle(radar_msg.x, radar_msg.y);
+///     rbot::aim_smart(0, angle, 2.0)?;
+/// }
+/// ```
+pub fn aim_smart(component_id: i32, angle: f32, deadband: f32) -> Result<bool, MessageError> {
+    let current_aim = component_world_aim(component_id)?;
+    if rotations::angle_distance(angle, current_aim) <= deadband {
+        return Ok(false);
+    }
+    aim(component_id, angle)?;
+    Ok(true)
+}
+
 /// Aims the robot component towards the specified angle and waits for the
 /// aiming process to complete within a tolerance range.
 ///
@@ -207,6 +571,9 @@ pub fn aim(component_id: i32, angle: f32) -> Result<(), MessageError> {
 /// `component_id` is aimed towards the target `angle` within the specified
 /// `slack` tolerance range.
 ///
+/// While the component is still far from the target, the rotation is checked
+/// less often, which saves messages to the game server on large turns.
+///
 /// # Arguments
 ///
 /// * `component_id` - The identifier of the robot component to aim.
@@ -232,10 +599,159 @@ pub fn aim(component_id: i32, angle: f32) -> Result<(), MessageError> {
 /// ```
 pub fn await_aim(component_id: i32, angle: f32, slack: f32) -> Result<(), MessageError> {
     aim(component_id, angle)?;
-    while !at_rotation(component_id, angle, slack)? {
-        sleep(0.01);
+    let target_angle = rotations::transform_rotation_to_component_normalized(component_id, angle);
+    loop {
+        let current_rotation = state()?.angle;
+        if rotations::angle_within(target_angle, current_rotation, slack) {
+            return Ok(());
+        }
+        let remaining = rotations::angle_distance(target_angle, current_rotation) - slack;
+        sleep(aim_poll_secs(remaining));
     }
-    Ok(())
+}
+
+/// Aims a robot component towards the specified angle, turning in the given direction.
+///
+/// `aim` always turns the robot the shorter way. To sweep across a target,
+/// e.g. with sticky fire, it can be necessary to turn the long way instead.
+/// If the shorter way is not in the requested `direction`, this function first
+/// rotates towards a waypoint halfway along the long way, and waits until the
+/// remaining rotation is shorter in the requested direction. Only then does it
+/// aim at `angle`, so in that case it blocks for part of the rotation. If the
+/// robot does not get there within twice the time expected at
+/// `DEFAULT_ROTATION_RATE`, it is considered stuck.
+///
+/// # Arguments
+///
+/// * `component_id` - The identifier of the robot component to aim.
+/// * `angle` - The target angle in degrees, where 0 degrees points towards the right.
+/// * `direction` - The direction in which to turn the robot.
+///
+/// # Returns
+///
+/// Returns `Ok(())` once the final rotation has been sent,
+/// `Err(MessageError::Timeout)` if the robot got stuck on the way to the
+/// waypoint, or another error of type `MessageError` if there was a problem
+/// during communication with the game server.
+///
+/// # Examples
+///
+/// ```
+/// use rbot::rotations::TurnDirection;
+///
+/// // Sweep clockwise across the enemy while firing.
+/// rbot::use_component(0, true)?;
+/// rbot::aim_directional(0, 90.0, TurnDirection::Clockwise)?;
+/// ```
+pub fn aim_directional(
+    component_id: i32,
+    angle: f32,
+    direction: rotations::TurnDirection,
+) -> Result<(), MessageError> {
+    let target_angle = rotations::transform_rotation_to_component_normalized(component_id, angle);
+    let current_rotation = state()?.angle;
+    let turn = rotations::shortest_turn(current_rotation, target_angle);
+    if turn.is_none() || turn == Some(direction) {
+        return rotate(target_angle);
+    }
+
+    let long_way = 360.0 - rotations::angle_distance(current_rotation, target_angle);
+    let waypoint = match direction {
+        rotations::TurnDirection::CounterClockwise => current_rotation + long_way / 2.0,
+        rotations::TurnDirection::Clockwise => current_rotation - long_way / 2.0,
+    };
+    rotate(rotations::normalize_angle(waypoint))?;
+    await_action()?;
+    await_until_timeout(
+        || {
+            let turn = rotations::shortest_turn(state()?.angle, target_angle);
+            Ok(turn.is_none() || turn == Some(direction))
+        },
+        poll_interval(),
+        aim_timeout_secs(long_way / 2.0, DEFAULT_ROTATION_RATE, DIRECTIONAL_AIM_FUDGE),
+    )?;
+    rotate(target_angle)
+}
+
+/// The factor by which the rotation to the waypoint of `aim_directional` may
+/// exceed its expected duration.
+const DIRECTIONAL_AIM_FUDGE: f32 = 2.0;
+
+/// The share of the expected remaining rotation time `await_aim` sleeps for.
+const AIM_POLL_FRACTION: f32 = 0.5;
+
+/// How long `await_aim` sleeps before checking the rotation again.
+///
+/// Large turns obviously take a while, so instead of polling at the fixed
+/// `poll_interval`, sleep for a share of the time the remaining `degrees`
+/// should take at `DEFAULT_ROTATION_RATE`. Close to the target this falls back
+/// to `poll_interval`, so the final precision is unchanged.
+fn aim_poll_secs(degrees: f32) -> f32 {
+    (degrees / DEFAULT_ROTATION_RATE * AIM_POLL_FRACTION).max(poll_interval())
+}
+
+/// Best-known rotation rate of a robot in degrees per second.
+///
+/// The game server is authoritative for the actual rate, which may depend on
+/// the robot's build.
+pub const DEFAULT_ROTATION_RATE: f32 = 180.0;
+
+/// Aims the robot component towards the specified angle and waits for the
+/// aiming process to complete, failing if it takes much longer than expected.
+///
+/// This function behaves like `await_aim`, but estimates how long the rotation
+/// should take from the angular distance and `rotation_rate_deg_per_sec`. If
+/// the component is not aimed within `distance / rate * fudge` seconds, it is
+/// considered stuck and the function returns `MessageError::Timeout`. The
+/// timeout only starts once the aim command has been applied in the next
+/// action loop, and is at least `poll_interval`, so short turns are not
+/// reported as stuck before they could begin.
+///
+/// # Arguments
+///
+/// * `component_id` - The identifier of the robot component to aim.
+/// * `angle` - The target angle in degrees, where 0 degrees points towards the right.
+/// * `slack` - The allowed tolerance (slack) in degrees for the aiming process.
+/// * `rotation_rate_deg_per_sec` - The expected rotation rate, e.g. `DEFAULT_ROTATION_RATE`.
+/// * `fudge` - The factor by which the expected duration may be exceeded.
+///
+/// # Returns
+///
+/// Returns `Ok(())` if the aiming process completes in time,
+/// `Err(MessageError::Timeout)` if it does not, or another error of type
+/// `MessageError` if there was a problem during communication with the game
+/// server.
+///
+/// # Examples
+///
+/// ```
+/// // Allow the rotation to take twice as long as expected.
+/// rbot::await_aim_bounded(0, 90.0, 0.5, rbot::DEFAULT_ROTATION_RATE, 2.0)?;
+/// ```
+pub fn await_aim_bounded(
+    component_id: i32,
+    angle: f32,
+    slack: f32,
+    rotation_rate_deg_per_sec: f32,
+    fudge: f32,
+) -> Result<(), MessageError> {
+    let distance = rotations::angle_distance(angle, component_world_aim(component_id)?);
+    let timeout = aim_timeout_secs(distance, rotation_rate_deg_per_sec, fudge);
+    aim(component_id, angle)?;
+    await_action()?;
+    await_until_timeout(
+        || at_rotation(component_id, angle, slack),
+        poll_interval(),
+        timeout,
+    )
+}
+
+/// How long a rotation by `degrees` may take before it is considered stuck.
+///
+/// This is the expected duration at `rotation_rate_deg_per_sec` times `fudge`,
+/// but at least `poll_interval`.
+fn aim_timeout_secs(degrees: f32, rotation_rate_deg_per_sec: f32, fudge: f32) -> f32 {
+    (degrees / rotation_rate_deg_per_sec * fudge).max(poll_interval())
 }
 
 /// Waits for the specified robot component's cooldown.
@@ -262,73 +778,399 @@ pub fn await_aim(component_id: i32, angle: f32, slack: f32) -> Result<(), Messag
 /// ```
 pub fn await_component(component_id: i32) -> Result<(), MessageError> {
     await_action()?;
-    while component_state(component_id)?.cooldown > 0.0 {
-        sleep(0.01);
-    }
-    Ok(())
+    await_until(
+        || Ok(component_state(component_id)?.cooldown <= 0.0),
+        poll_interval(),
+    )
 }
 
-/// Awaits until a component is no longer activated.
+/// Blocks execution until any of several components is ready to be used.
 ///
-/// This function blocks execution until the specified component is no longer activated,
-/// repeatedly checking the component's state and sleeping for a short duration between checks.
+/// The components are checked in the given order, and the first one whose
+/// cooldown is over is returned. This keeps the rate of fire up in a "fire
+/// whichever is ready first" loop, instead of waiting for each cooldown in turn.
 ///
-/// # Parameters
+/// # Arguments
 ///
-/// * `component_id` - The ID of the component to monitor.
+/// * `component_ids` - The identifiers of the components to wait for.
 ///
 /// # Returns
 ///
-/// * `Ok(())` - if the component is no longer activated.
-/// * `Err(MessageError)` - if an error occurs, with details about the specific error.
-///
-/// # Errors
-///
-/// This function can return the following errors:
-///
-/// * `MessageError::BadCommand` - if there is an issue with the command sent to the game.
-/// * `MessageError::InvalidResponse` - if the response from the game is not as expected.
+/// The identifier of a ready component, `MessageError::InvalidArgument` if
+/// `component_ids` is empty, or an error of type `MessageError` if there was a
+/// problem during communication with the game server.
 ///
 /// # Examples
 ///
 /// ```
-/// // Wait for the component with ID 0 to no longer be activated.
-/// rbot::await_not_activated(0)?;
+/// loop {
+///     let id = rbot::await_any_component(&[0, 1, 2])?;
+///     rbot::use_component(id, false)?;
+/// }
 /// ```
-pub fn await_not_activated(component_id: i32) -> Result<(), MessageError> {
+pub fn await_any_component(component_ids: &[i32]) -> Result<i32, MessageError> {
+    if component_ids.is_empty() {
+        return Err(MessageError::InvalidArgument(
+            "at least one component id is required",
+        ));
+    }
     await_action()?;
-    while component_state(component_id)?.is_activated {
-        sleep(0.01);
+    loop {
+        for id in component_ids {
+            if component_state(*id)?.cooldown <= 0.0 {
+                return Ok(*id);
+            }
+        }
+        sleep(poll_interval());
     }
-    Ok(())
 }
 
-/// Retrieves the current state of the robot.
+/// Fires a component exactly `count` times, waiting for its cooldown before each shot.
 ///
-/// The robot state includes information such as its angle, velocity, motherboard health,
-/// and active buffs on the main board. To extract detailed state information about specific
-/// components, use the `component_state` function.
+/// This is the middle ground between firing once and sticky fire. The function
+/// blocks across all the cooldowns until the last shot has been fired.
+///
+/// # Arguments
+///
+/// * `component_id` - The identifier of the component to fire.
+/// * `count` - The number of times to fire the component.
 ///
 /// # Returns
 ///
-/// Returns a `Result` containing `msg::RMsgState` representing the current state of the robot
-/// if the retrieval is successful, or an error of type `MessageError` if there was a problem
-/// retrieving the robot state.
+/// Returns `Ok(())` once every shot has been fired, or an error of type
+/// `MessageError` if there was a problem during communication with the game
+/// server.
 ///
 /// # Examples
 ///
 /// ```
-/// let robot_state = state()?;
+/// // Fire three shots with component 0.
+/// rbot::fire_burst(0, 3)?;
 /// ```
-pub fn state() -> Result<msg::RMsgState, MessageError> {
-    let msg_use = msg::MsgState { value: 0 };
-    let response = hostfn::send_message(&msg_use);
-
-    match response {
-        MessageType::Error(m) => Err(MessageError::BadCommand(m.error_code)),
-        MessageType::RState(m) => Ok(m),
-        _ => Err(MessageError::InvalidResponse),
+pub fn fire_burst(component_id: i32, count: u32) -> Result<(), MessageError> {
+    for _ in 0..count {
+        await_component(component_id)?;
+        use_component(component_id, false)?;
     }
+    Ok(())
+}
+
+/// The allowed aim error in degrees for each shot of `fire_spread`.
+const SPREAD_AIM_SLACK: f32 = 1.0;
+
+/// Fires a component several times with a random aim offset for each shot.
+///
+/// Before each shot, the function waits for the component's cooldown, then
+/// aims at `center_angle` plus a random offset of up to half of `spread_deg`
+/// in either direction and waits until it is aimed. This suppresses an area
+/// rather than a single point. The function blocks until the last shot has been
+/// fired. The offsets are drawn from `rng`, so a fixed seed gives a
+/// reproducible pattern.
+///
+/// # Arguments
+///
+/// * `component_id` - The identifier of the component to fire.
+/// * `center_angle` - The angle in degrees at the center of the spread.
+/// * `spread_deg` - The total width of the spread in degrees.
+/// * `shots` - The number of times to fire the component.
+/// * `rng` - The random number generator for the aim offsets.
+///
+/// # Returns
+///
+/// Returns `Ok(())` once every shot has been fired, or an error of type
+/// `MessageError` if there was a problem during communication with the game
+/// server.
+///
+/// # Examples
+///
+/// ```
+/// let mut rng = rbot::Rng::new(42);
+/// // Fire five shots within 20 degrees around the top.
+/// rbot::fire_spread(0, 90.0, 20.0, 5, &mut rng)?;
+/// ```
+pub fn fire_spread(
+    component_id: i32,
+    center_angle: f32,
+    spread_deg: f32,
+    shots: u32,
+    rng: &mut Rng,
+) -> Result<(), MessageError> {
+    for _ in 0..shots {
+        await_component(component_id)?;
+        let angle = rng.jitter(center_angle, spread_deg / 2.0);
+        await_aim(component_id, angle, SPREAD_AIM_SLACK)?;
+        use_component(component_id, false)?;
+    }
+    Ok(())
+}
+
+/// The allowed aim error in degrees for `assign_and_fire` to fire a component.
+const ASSIGN_FIRE_SLACK: f32 = 5.0;
+
+/// Aims components at their assigned world angles and fires those that are ready.
+///
+/// All components are mounted on the robot and turn with it, so a single
+/// rotation aims every component at once. This function rotates the robot
+/// towards the first assignment, then fires every assigned component that is
+/// off cooldown and already aimed within a few degrees of its assigned angle.
+/// Assignments that match the spacing of the components, such as two enemies
+/// on opposite sides for components 0 and 2, can therefore be served at once.
+///
+/// The function does not block on the rotation or on cooldowns, so it stays
+/// responsive when called every tick.
+///
+/// # Arguments
+///
+/// * `assignments` - Pairs of component id and world angle in degrees.
+///
+/// # Returns
+///
+/// Returns `Ok(())` if all commands were sent successfully, or an error of type
+/// `MessageError` if there was a problem during communication with the game
+/// server.
+///
+/// # Examples
+///
+/// ```
+/// // Aim component 0 at an enemy to the right and component 2 at one to the left.
+/// loop {
+///     rbot::assign_and_fire(&[(0, 0.0), (2, 180.0)])?;
+/// }
+/// ```
+pub fn assign_and_fire(assignments: &[(i32, f32)]) -> Result<(), MessageError> {
+    let Some((first_id, first_angle)) = assignments.first() else {
+        return Ok(());
+    };
+    aim(*first_id, *first_angle)?;
+
+    for (component_id, angle) in assignments {
+        let ready = component_state(*component_id)?.cooldown <= 0.0;
+        if ready && at_rotation(*component_id, *angle, ASSIGN_FIRE_SLACK)? {
+            use_component(*component_id, false)?;
+        }
+    }
+    Ok(())
+}
+
+/// Awaits until a component is no longer activated.
+///
+/// This function blocks execution until the specified component is no longer activated,
+/// repeatedly checking the component's state and sleeping for a short duration between checks.
+///
+/// # Parameters
+///
+/// * `component_id` - The ID of the component to monitor.
+///
+/// # Returns
+///
+/// * `Ok(())` - if the component is no longer activated.
+/// * `Err(MessageError)` - if an error occurs, with details about the specific error.
+///
+/// # Errors
+///
+/// This function can return the following errors:
+///
+/// * `MessageError::BadCommand` - if there is an issue with the command sent to the game.
+/// * `MessageError::InvalidResponse` - if the response from the game is not as expected.
+///
+/// # Examples
+///
+/// ```
+/// // Wait for the component with ID 0 to no longer be activated.
+/// rbot::await_not_activated(0)?;
+/// ```
+pub fn await_not_activated(component_id: i32) -> Result<(), MessageError> {
+    await_action()?;
+    await_until(
+        || Ok(!component_state(component_id)?.is_activated),
+        poll_interval(),
+    )
+}
+
+/// The default poll interval of the `await_*` functions in seconds.
+const DEFAULT_POLL_INTERVAL: f32 = 0.01;
+
+thread_local! {
+    /// The poll interval of the `await_*` functions in seconds.
+    static POLL_INTERVAL: std::cell::Cell<f32> = const { std::cell::Cell::new(DEFAULT_POLL_INTERVAL) };
+}
+
+/// Sets the interval at which the blocking `await_*` functions poll the game server.
+///
+/// A shorter interval makes functions such as `await_aim` react faster, at the
+/// cost of sending more messages to the game server. The default is 0.01
+/// seconds.
+///
+/// # Arguments
+///
+/// * `secs` - The duration in seconds to sleep between checks.
+///
+/// # Returns
+///
+/// Returns `Ok(())` if the interval was set, or
+/// `MessageError::InvalidArgument` if `secs` is not a positive, finite number,
+/// in which case the interval is left unchanged.
+///
+/// # Examples
+///
+/// ```
+/// // Poll less often to reduce traffic.
+/// rbot::set_poll_interval(0.05)?;
+/// rbot::await_component(0)?;
+/// ```
+pub fn set_poll_interval(secs: f32) -> Result<(), MessageError> {
+    if !secs.is_finite() || secs <= 0.0 {
+        return Err(MessageError::InvalidArgument(
+            "poll interval must be positive and finite",
+        ));
+    }
+    POLL_INTERVAL.with(|interval| interval.set(secs));
+    Ok(())
+}
+
+/// Returns the interval in seconds at which the blocking `await_*` functions
+/// poll the game server. See `set_poll_interval`.
+pub fn poll_interval() -> f32 {
+    POLL_INTERVAL.with(|interval| interval.get())
+}
+
+/// Blocks execution until a custom condition becomes true.
+///
+/// This function repeatedly evaluates `cond`, sleeping for `poll_secs` between
+/// checks, until it returns `true`. It is the general form of the loops used by
+/// `await_component` and `await_aim`, and is handy for conditions such as
+/// waiting for the health to drop or for an enemy to come within range.
+///
+/// # Arguments
+///
+/// * `cond` - The condition to evaluate. Errors returned from it are propagated.
+/// * `poll_secs` - The duration in seconds to sleep between checks.
+///
+/// # Returns
+///
+/// * `Ok(())` - once the condition returns `true`.
+/// * `Err(MessageError)` - if evaluating the condition fails.
+///
+/// # Examples
+///
+/// ```
+/// // Wait until component 0 has been activated.
+/// rbot::await_until(|| Ok(rbot::component_state(0)?.is_activated), poll_interval())?;
+/// ```
+pub fn await_until<F: FnMut() -> Result<bool, MessageError>>(
+    mut cond: F,
+    poll_secs: f32,
+) -> Result<(), MessageError> {
+    while !cond()? {
+        sleep(poll_secs);
+    }
+    Ok(())
+}
+
+/// Blocks execution until a custom condition becomes true or a timeout expires.
+///
+/// This function behaves like `await_until`, but gives up once `timeout_secs`
+/// seconds of game time have passed without the condition becoming true.
+///
+/// # Arguments
+///
+/// * `cond` - The condition to evaluate. Errors returned from it are propagated.
+/// * `poll_secs` - The duration in seconds to sleep between checks.
+/// * `timeout_secs` - The maximum duration in seconds to wait for the condition.
+///
+/// # Returns
+///
+/// * `Ok(())` - once the condition returns `true`.
+/// * `Err(MessageError::Timeout)` - if the timeout expires first.
+/// * `Err(MessageError)` - if evaluating the condition or reading the time fails.
+///
+/// # Examples
+///
+/// ```
+/// // Wait at most two seconds for component 0 to be ready.
+/// match rbot::await_until_timeout(|| Ok(rbot::component_state(0)?.cooldown <= 0.0), 0.01, 2.0) {
+///     Ok(()) => rbot::use_component(0, false)?,
+///     Err(rbot::errors::MessageError::Timeout) => rbot::print("Component 0 is still cooling down."),
+///     Err(e) => return Err(e),
+/// }
+/// ```
+pub fn await_until_timeout<F: FnMut() -> Result<bool, MessageError>>(
+    mut cond: F,
+    poll_secs: f32,
+    timeout_secs: f32,
+) -> Result<(), MessageError> {
+    let start = time()?;
+    while !cond()? {
+        if time()? - start >= timeout_secs {
+            return Err(MessageError::Timeout);
+        }
+        sleep(poll_secs);
+    }
+    Ok(())
+}
+
+/// Retrieves the current state of the robot.
+///
+/// The robot state includes information such as its angle, velocity, motherboard health,
+/// and active buffs on the main board. To extract detailed state information about specific
+/// components, use the `component_state` function.
+///
+/// # Returns
+///
+/// Returns a `Result` containing `msg::RMsgState` representing the current state of the robot
+/// if the retrieval is successful, or an error of type `MessageError` if there was a problem
+/// retrieving the robot state.
+///
+/// # Examples
+///
+/// ```
+/// let robot_state = state()?;
+/// ```
+pub fn state() -> Result<msg::RMsgState, MessageError> {
+    let msg_use = msg::MsgState { value: 0 };
+    let response = hostfn::send_message(&msg_use)?;
+
+    match response {
+        MessageType::Error(m) => Err(MessageError::BadCommand(m.error_code)),
+        MessageType::RState(m) => Ok(m),
+        _ => Err(MessageError::InvalidResponse),
+    }
+}
+
+/// Runs an action when the robot's motherboard health drops below a threshold.
+///
+/// This function reads the robot state once and invokes `action` only if the
+/// motherboard health is below `threshold`. Call it every tick to guard the
+/// robot, for example by activating the force field when it is low on health.
+///
+/// # Arguments
+///
+/// * `threshold` - The motherboard health below which `action` is invoked.
+/// * `action` - The action to run when the health is low.
+///
+/// # Returns
+///
+/// Returns `Ok(())` if the health was not low or `action` succeeded, or an
+/// error of type `MessageError` if reading the state or running `action` failed.
+///
+/// # Examples
+///
+/// ```
+/// loop {
+///     rbot::on_low_health(30.0, || {
+///         rbot::modules::force_field()?;
+///         Ok(())
+///     })?;
+/// }
+/// ```
+pub fn on_low_health<F: FnMut() -> Result<(), MessageError>>(
+    threshold: f32,
+    mut action: F,
+) -> Result<(), MessageError> {
+    if state()?.health < threshold {
+        action()?;
+    }
+    Ok(())
 }
 
 /// Retrieves the current status of the specified robot component.
@@ -353,7 +1195,7 @@ pub fn state() -> Result<msg::RMsgState, MessageError> {
 /// ```
 pub fn component_state(component_id: i32) -> Result<msg::RMsgComponentStatus, MessageError> {
     let msg_comp_state = msg::MsgComponentStatusQuery { component_id };
-    let response = hostfn::send_message(&msg_comp_state);
+    let response = hostfn::send_message(&msg_comp_state)?;
 
     match response {
         MessageType::Error(m) => Err(MessageError::BadCommand(m.error_code)),
@@ -362,73 +1204,388 @@ pub fn component_state(component_id: i32) -> Result<msg::RMsgComponentStatus, Me
     }
 }
 
-/// Pauses the main thread for the specified duration in seconds.
-///
-/// This function blocks the execution of the main thread, causing it to sleep
-/// for the given `seconds` before resuming.
+/// Retrieves the current status of several robot components at once.
+///
+/// The game server does not yet provide a multi-component query, so this sends
+/// one status request per component. It exists so code polling many
+/// components only needs a single call, and can pick up a batched request
+/// without changes once the protocol offers one.
+///
+/// # Arguments
+///
+/// * `component_ids` - The identifiers of the robot components for which to retrieve the state.
+///
+/// # Returns
+///
+/// Returns a `Result` containing the statuses in the same order as
+/// `component_ids`, or the first error of type `MessageError` encountered while
+/// retrieving them.
+///
+/// # Examples
+///
+/// ```
+/// let states = rbot::component_states(&[0, 1, 2, 3])?;
+/// let ready = states.iter().filter(|s| s.cooldown <= 0.0).count();
+/// ```
+pub fn component_states(
+    component_ids: &[i32],
+) -> Result<Vec<msg::RMsgComponentStatus>, MessageError> {
+    component_ids
+        .iter()
+        .map(|id| component_state(*id))
+        .collect()
+}
+
+/// Checks whether a robot component is currently activated.
+///
+/// # Arguments
+///
+/// * `component_id` - The identifier of the robot component.
+///
+/// # Returns
+///
+/// The activation state from `component_state`, or an error of type
+/// `MessageError` if there was a problem during communication with the game
+/// server.
+///
+/// # Examples
+///
+/// ```
+/// if !rbot::is_component_active(0)? {
+///     rbot::use_component(0, true)?;
+/// }
+/// ```
+pub fn is_component_active(component_id: i32) -> Result<bool, MessageError> {
+    Ok(component_state(component_id)?.is_activated)
+}
+
+/// Checks whether a robot component is destroyed, i.e. has no health left.
+///
+/// # Arguments
+///
+/// * `component_id` - The identifier of the robot component.
+///
+/// # Returns
+///
+/// `true` if the component's health is 0 or less, or an error of type
+/// `MessageError` if there was a problem during communication with the game
+/// server.
+///
+/// # Examples
+///
+/// ```
+/// if !rbot::is_component_destroyed(2)? {
+///     rbot::use_component(2, false)?;
+/// }
+/// ```
+pub fn is_component_destroyed(component_id: i32) -> Result<bool, MessageError> {
+    Ok(component_state(component_id)?.health <= 0.0)
+}
+
+/// Fires several components once each.
+///
+/// # Arguments
+///
+/// * `component_ids` - The identifiers of the components to fire.
+/// * `skip_destroyed` - Whether to skip destroyed components instead of
+///   sending commands for them. Checking costs an extra message per component.
+///
+/// # Returns
+///
+/// Returns `Ok(())` if all commands were sent successfully, or an error of
+/// type `MessageError` if there was a problem during communication with the
+/// game server.
+///
+/// # Examples
+///
+/// ```
+/// rbot::fire_all(&[0, 1, 2, 3], true)?;
+/// ```
+pub fn fire_all(component_ids: &[i32], skip_destroyed: bool) -> Result<(), MessageError> {
+    for id in component_ids {
+        if skip_destroyed && is_component_destroyed(*id)? {
+            continue;
+        }
+        use_component(*id, false)?;
+    }
+    Ok(())
+}
+
+/// Sums the health of several components.
+///
+/// # Arguments
+///
+/// * `component_ids` - The identifiers of the components.
+/// * `skip_destroyed` - Whether to leave out destroyed components, whose
+///   health may be reported as negative.
+///
+/// # Returns
+///
+/// The total health, or an error of type `MessageError` if there was a
+/// problem during communication with the game server.
+///
+/// # Examples
+///
+/// ```
+/// let health = rbot::total_health(&[0, 1, 2, 3], true)?;
+/// ```
+pub fn total_health(component_ids: &[i32], skip_destroyed: bool) -> Result<f32, MessageError> {
+    Ok(component_states(component_ids)?
+        .iter()
+        .map(|state| state.health)
+        .filter(|health| !skip_destroyed || *health > 0.0)
+        .sum())
+}
+
+/// The shortest duration in seconds `sleep` passes to the host.
+pub const MIN_SLEEP_SECS: f32 = 0.001;
+
+/// Pauses the main thread for the specified duration in seconds.
+///
+/// This function blocks the execution of the main thread, causing it to sleep
+/// for the given `seconds` before resuming. Durations shorter than
+/// `MIN_SLEEP_SECS` are raised to it, so tight loops don't hammer the host
+/// scheduler. Zero, negative and NaN durations return immediately.
+///
+/// # Arguments
+///
+/// * `seconds` - The duration in seconds for which the thread should sleep.
+///
+/// # Examples
+///
+/// ```
+/// rbot::print("Starting to wait...");
+/// rbot::sleep(2.5);
+/// rbot::print("Waited for 2.5 seconds.");
+/// ```
+pub fn sleep(seconds: f32) {
+    if seconds.is_nan() || seconds <= 0.0 {
+        return;
+    }
+    unsafe { hostfn::bot_sleep(seconds.max(MIN_SLEEP_SECS)) };
+}
+
+/// Generates a pseudo-random floating-point number between 0 (inclusive) and 1 (includive).
+///
+/// This function returns a random number that falls within the interval [0, 1].
+///
+/// # Returns
+///
+/// A random floating-point number in the range [0, 1].
+///
+/// # Examples
+///
+/// ```
+/// let random_number = random();
+/// fn main() {
+///     let num = random();
+///     println!("Random number between 0 and 1: {}", num);
+/// }
+///
+/// fn random() -> f32 {
+///     use rand::Rng;
+///     let mut rng = rand::thread_rng();
+///     rng.gen::<f32>()
+/// }
+/// ```
+pub fn random() -> f32 {
+    unsafe { hostfn::bot_random() }
+}
+
+/// Generates a pseudo-random floating-point number between `min` and `max` (inclusive).
+///
+/// # Arguments
+///
+/// * `min` - The lower bound of the range.
+/// * `max` - The upper bound of the range.
+///
+/// # Examples
+///
+/// ```
+/// // Move in a random direction.
+/// let angle = rbot::random_range(0.0, 360.0);
+/// let [x, y] = rbot::conversions::angle_to_xy(angle);
+/// rbot::velocity(x, y, 1.0)?;
+/// ```
+pub fn random_range(min: f32, max: f32) -> f32 {
+    min + (max - min) * random()
+}
+
+/// Generates a pseudo-random index between 0 (inclusive) and `len` (exclusive).
+///
+/// `random` can return exactly 1, so scaling it by `len` could produce `len`
+/// itself. This function clamps the result so it is always a valid index.
+///
+/// # Arguments
+///
+/// * `len` - The number of elements to pick an index from. Must be greater than 0.
+///
+/// # Examples
+///
+/// ```
+/// let component_ids = [0, 1, 2, 3];
+/// let id = component_ids[rbot::random_index(component_ids.len())];
+/// ```
+pub fn random_index(len: usize) -> usize {
+    ((random() * len as f32) as usize).min(len.saturating_sub(1))
+}
+
+/// Logs a message to the game console in the programming scene.
+///
+/// This function writes the specified `string` message to a log file that is displayed
+/// in the game's console in the programming scene.
+///
+/// # Arguments
+///
+/// * `string` - The string message to be logged and displayed in the game console.
+///
+/// # Examples
+///
+/// ```
+/// rbot::print("Hello World");
+/// ```
+pub fn print(string: &str) {
+    print_bytes(string.as_bytes());
+}
+
+/// Logs raw bytes to the game console in the programming scene.
+///
+/// Unlike `print`, the bytes are not required to be valid UTF-8 and are passed
+/// to the host unchanged. This is useful for dumping raw protocol data while
+/// diagnosing serialization issues.
+///
+/// # Arguments
+///
+/// * `bytes` - The bytes to be logged.
+///
+/// # Examples
+///
+/// ```
+/// // Not valid UTF-8, but still forwarded to the console.
+/// rbot::print_bytes(&[0x7b, 0xff, 0x7d]);
+/// ```
+pub fn print_bytes(bytes: &[u8]) {
+    let size = bytes.len() as i32;
+    let bytes_ptr = hostfn::wasm_ptr(bytes.as_ptr());
+    unsafe {
+        hostfn::dbg_log(bytes_ptr, size);
+    }
+}
+
+/// Logs any value implementing `Display` to the game console in the programming scene.
 ///
 /// # Arguments
 ///
-/// * `seconds` - The duration in seconds for which the thread should sleep.
+/// * `value` - The value to be formatted and logged.
 ///
 /// # Examples
 ///
 /// ```
-/// rbot::print("Starting to wait...");
-/// rbot::sleep(2.5);
-/// rbot::print("Waited for 2.5 seconds.");
+/// rbot::print_display(&rbot::time()?);
 /// ```
-pub fn sleep(seconds: f32) {
-    unsafe { hostfn::bot_sleep(seconds) };
+pub fn print_display<T: std::fmt::Display>(value: &T) {
+    print(&value.to_string());
 }
 
-/// Generates a pseudo-random floating-point number between 0 (inclusive) and 1 (includive).
-///
-/// This function returns a random number that falls within the interval [0, 1].
-///
-/// # Returns
+/// Collects log lines to write them to the game console in a single call.
 ///
-/// A random floating-point number in the range [0, 1].
+/// Every `print` call crosses from the robot's sandbox to the host, which is
+/// slow when logging in a tight loop. A `LogBuffer` accumulates the lines and
+/// `flush` writes them all with one `print`. Lines that are not flushed are
+/// lost if the robot panics. The global buffer used by `buffered_println!` is
+/// flushed by the panic hook of `install_panic_hook`.
 ///
 /// # Examples
 ///
 /// ```
-/// let random_number = random();
-/// fn main() {
-///     let num = random();
-///     println!("Random number between 0 and 1: {}", num);
-/// }
-///
-/// fn random() -> f32 {
-///     use rand::Rng;
-///     let mut rng = rand::thread_rng();
-///     rng.gen::<f32>()
+/// let mut log = rbot::LogBuffer::new();
+/// for id in 0..4 {
+///     log.push_line(&format!("Component {id}: {}", rbot::component_state(id)?.health));
 /// }
+/// log.flush();
 /// ```
-pub fn random() -> f32 {
-    unsafe { hostfn::bot_random() }
+#[derive(Debug, Default)]
+pub struct LogBuffer {
+    text: String,
 }
 
-/// Logs a message to the game console in the programming scene.
+impl LogBuffer {
+    /// Creates an empty buffer.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Appends `line` followed by a newline to the buffer.
+    pub fn push_line(&mut self, line: &str) {
+        self.text.push_str(line);
+        self.text.push('\n');
+    }
+
+    /// Writes the buffered lines to the game console and empties the buffer.
+    pub fn flush(&mut self) {
+        if !self.text.is_empty() {
+            print(&self.text);
+            self.text.clear();
+        }
+    }
+}
+
+thread_local! {
+    static LOG_BUFFER: std::cell::RefCell<LogBuffer> = std::cell::RefCell::new(LogBuffer::new());
+}
+
+/// Appends a line to the global log buffer used by `buffered_println!`.
+pub fn buffer_line(line: &str) {
+    LOG_BUFFER.with(|buffer| buffer.borrow_mut().push_line(line));
+}
+
+/// Writes the lines of the global log buffer to the game console.
 ///
-/// This function writes the specified `string` message to a log file that is displayed
-/// in the game's console in the programming scene.
+/// # Examples
 ///
-/// # Arguments
+/// ```
+/// loop {
+///     rbot::buffered_println!("Tick {}", rbot::next_tick());
+///     if rbot::every_n_ticks(100) {
+///         rbot::flush_log();
+///     }
+/// }
+/// ```
+pub fn flush_log() {
+    // The panic hook may run while the buffer is borrowed, so skip it then.
+    LOG_BUFFER.with(|buffer| {
+        if let Ok(mut buffer) = buffer.try_borrow_mut() {
+            buffer.flush();
+        }
+    });
+}
+
+/// Routes panic messages to the game console in the programming scene.
 ///
-/// * `string` - The string message to be logged and displayed in the game console.
+/// By default a panic inside the robot's sandbox stops the robot without any
+/// output. After calling this function, the panic message and its location in
+/// the source code are logged with `print` before the robot stops, after any
+/// lines still in the `buffered_println!` buffer. Call it once at the top of
+/// `main`.
 ///
 /// # Examples
 ///
 /// ```
-/// rbot::print("Hello World");
+/// pub fn main() {
+///     rbot::install_panic_hook();
+///
+///     // Panics are now visible in the game console.
+///     let radar_msg = rbot::modules::radar().expect("failed to get radar message");
+/// }
 /// ```
-pub fn print(string: &str) {
-    let size = string.len() as i32;
-    let bytes_ptr = string.as_ptr() as i32;
-    unsafe {
-        hostfn::dbg_log(bytes_ptr, size);
-    }
+pub fn install_panic_hook() {
+    std::panic::set_hook(Box::new(|info| report_panic(info)));
+}
+
+/// Logs a panic as the hook of `install_panic_hook` does.
+fn report_panic(info: &dyn std::fmt::Display) {
+    flush_log();
+    print(&format!("{info}\n"));
 }
 
 /// Retrieves the current timestamp in seconds.
@@ -448,7 +1605,7 @@ pub fn print(string: &str) {
 /// ```
 pub fn time() -> Result<f32, MessageError> {
     let msg_time = msg::MsgTime { value: 0 };
-    let response = hostfn::send_message(&msg_time);
+    let response = hostfn::send_message(&msg_time)?;
 
     match response {
         MessageType::Error(m) => Err(MessageError::BadCommand(m.error_code)),
@@ -457,11 +1614,59 @@ pub fn time() -> Result<f32, MessageError> {
     }
 }
 
+/// The number of ticks counted with `next_tick`.
+static TICK: AtomicU64 = AtomicU64::new(0);
+
+/// Advances the tick counter by one and returns the new tick count.
+///
+/// Call this once per iteration of the main loop. Unlike `time`, the tick
+/// counter does not query the game server, so it is a cheap way to run code
+/// periodically, see `every_n_ticks`.
+///
+/// # Examples
+///
+/// ```
+/// loop {
+///     rbot::next_tick();
+///     if rbot::every_n_ticks(20) {
+///         let scan_msg = rbot::modules::scan()?;
+///     }
+/// }
+/// ```
+pub fn next_tick() -> u64 {
+    TICK.fetch_add(1, Ordering::Relaxed) + 1
+}
+
+/// Returns the number of ticks counted with `next_tick` so far.
+pub fn tick_count() -> u64 {
+    TICK.load(Ordering::Relaxed)
+}
+
+/// Checks whether the current tick count is a multiple of `n`.
+///
+/// # Arguments
+///
+/// * `n` - The period in ticks. A period of 0 never matches.
+///
+/// # Returns
+///
+/// `true` on every `n`-th tick counted with `next_tick`.
+pub fn every_n_ticks(n: u64) -> bool {
+    n != 0 && tick_count().is_multiple_of(n)
+}
+
 /// Awaits the completion of a game action trigger.
 ///
 /// This function sends a message to the game to await an action, which is crucial
 /// for checking the status of a component after it has been used.
 ///
+/// Commands such as `use_component` or a module call are queued and only
+/// performed in the robot's next action loop. Until then, cooldowns and
+/// activation states still show their old values. `await_action` blocks until
+/// the queued actions have been performed, so any status read afterwards
+/// reflects them. This is why `await_component` and `await_module` call it
+/// before polling, and why custom polling loops should do the same.
+///
 /// # Returns
 ///
 /// * `Ok(())` - if the action completes successfully.
@@ -491,11 +1696,24 @@ pub fn time() -> Result<f32, MessageError> {
 /// rbot::await_action()?;
 ///
 /// // Now, retrieving the component state will show the updated cooldown.
-/// let component_state = rbot::component_state()?;
+/// let component_state = rbot::component_state(0)?;
+/// ```
+///
+/// The same ordering applies to a hand-written module loop:
+/// ```
+/// use rbot::modules::Module;
+///
+/// rbot::modules::mine()?;
+///
+/// // Without this, the status below could still report the old cooldown.
+/// rbot::await_action()?;
+/// while rbot::modules::status(Module::Mine)?.cooldown > 0.0 {
+///     rbot::sleep(0.01);
+/// }
 /// ```
 pub fn await_action() -> Result<(), MessageError> {
     let msg_await_action = msg::MsgAwaitAction { value: 0 };
-    let response = hostfn::send_message(&msg_await_action);
+    let response = hostfn::send_message(&msg_await_action)?;
 
     match response {
         MessageType::Error(m) => Err(MessageError::BadCommand(m.error_code)),
@@ -503,3 +1721,879 @@ pub fn await_action() -> Result<(), MessageError> {
         _ => Err(MessageError::InvalidResponse),
     }
 }
+
+/// Runs every action and collects all errors instead of stopping at the first one.
+///
+/// This function is useful for volleys and multi-step maneuvers where each step
+/// should be attempted even if an earlier one failed, while still reporting
+/// everything that went wrong.
+///
+/// # Arguments
+///
+/// * `actions` - The actions to run, in order.
+///
+/// # Returns
+///
+/// * `Ok(())` - if every action succeeded.
+/// * `Err(Vec<MessageError>)` - containing the errors of all failed actions, in order.
+///
+/// # Examples
+///
+/// ```
+/// let result = rbot::try_all(vec![
+///     Box::new(|| rbot::use_component(0, false)),
+///     Box::new(|| rbot::use_component(1, false)),
+///     Box::new(|| rbot::use_component(2, false)),
+/// ]);
+///
+/// if let Err(errors) = result {
+///     rbot::print(&format!("{} components failed to fire.", errors.len()));
+/// }
+/// ```
+pub fn try_all(
+    actions: Vec<Box<dyn FnMut() -> Result<(), MessageError>>>,
+) -> Result<(), Vec<MessageError>> {
+    let errors: Vec<MessageError> = actions
+        .into_iter()
+        .filter_map(|mut action| action().err())
+        .collect();
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+/// A small seeded pseudo-random number generator (xorshift).
+///
+/// Unlike `random`, this generator does not need the host and produces the same
+/// sequence for the same seed, which makes random tactics such as aim jitter
+/// reproducible.
+///
+/// # Examples
+///
+/// ```
+/// let mut rng = rbot::Rng::new(42);
+/// let angle = rng.jitter(90.0, 5.0);
+/// rbot::aim(0, angle)?;
+/// ```
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    /// Creates a new generator from `seed`. A seed of 0 is replaced by a fixed
+    /// non-zero value, as xorshift would otherwise only produce zeros.
+    pub fn new(seed: u64) -> Self {
+        let state = if seed == 0 {
+            0x9E37_79B9_7F4A_7C15
+        } else {
+            seed
+        };
+        Self { state }
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut x = self.state;
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.state = x;
+        x
+    }
+
+    /// Generates a number between 0 (inclusive) and 1 (exclusive).
+    pub fn next_f32(&mut self) -> f32 {
+        (self.next_u64() >> 40) as f32 / (1u64 << 24) as f32
+    }
+
+    /// Generates a number between `min` (inclusive) and `max` (exclusive).
+    pub fn range(&mut self, min: f32, max: f32) -> f32 {
+        min + (max - min) * self.next_f32()
+    }
+
+    /// Generates a number within `amount` of `base`.
+    pub fn jitter(&mut self, base: f32, amount: f32) -> f32 {
+        base + self.range(-amount, amount)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hostfn::mock;
+
+    #[test]
+    fn rng_is_reproducible() {
+        let (mut a, mut b) = (Rng::new(42), Rng::new(42));
+        for _ in 0..100 {
+            assert_eq!(a.next_f32(), b.next_f32());
+        }
+        let (mut a, mut b) = (Rng::new(1), Rng::new(2));
+        let a: Vec<_> = (0..10).map(|_| a.next_f32()).collect();
+        let b: Vec<_> = (0..10).map(|_| b.next_f32()).collect();
+        assert_ne!(a, b);
+    }
+
+    #[test]
+    fn rng_zero_seed_does_not_get_stuck() {
+        let mut rng = Rng::new(0);
+        assert!((0..10).any(|_| rng.next_f32() != 0.0));
+    }
+
+    #[test]
+    fn rng_stays_in_range() {
+        let mut rng = Rng::new(7);
+        for _ in 0..1000 {
+            let unit = rng.next_f32();
+            assert!((0.0..1.0).contains(&unit));
+            let value = rng.range(-3.0, 5.0);
+            assert!((-3.0..5.0).contains(&value));
+            let jittered = rng.jitter(90.0, 5.0);
+            assert!((85.0..=95.0).contains(&jittered));
+        }
+    }
+
+    /// Answers as a component whose cooldown is `cooldown` before it fires
+    /// and `after` once a shot has been performed in an action loop.
+    fn component_cooling(cooldown: f32, after: f32) {
+        let (mut fired, mut performed) = (false, false);
+        mock::on_message(move |name, _| match name {
+            "MsgComponentStatusQuery" => MessageType::RComponentStatus(msg::RMsgComponentStatus {
+                health: 100.0,
+                cooldown: if performed { after } else { cooldown },
+                is_activated: false,
+            }),
+            "MsgUse" => {
+                fired = true;
+                MessageType::Empty(msg::MsgEmpty { value: 0 })
+            }
+            "MsgAwaitAction" => {
+                performed |= fired;
+                MessageType::Empty(msg::MsgEmpty { value: 0 })
+            }
+            _ => MessageType::Empty(msg::MsgEmpty { value: 0 }),
+        });
+    }
+
+    #[test]
+    fn fire_checked_reads_the_cooldown_after_the_shot() {
+        component_cooling(0.0, 1.5);
+        let outcome = fire_checked(0).unwrap();
+        assert_eq!(
+            outcome,
+            FireOutcome {
+                fired: true,
+                next_ready_in: 1.5
+            }
+        );
+    }
+
+    #[test]
+    fn fire_checked_does_not_fire_on_cooldown() {
+        component_cooling(0.5, 1.5);
+        let outcome = fire_checked(0).unwrap();
+        assert_eq!(
+            outcome,
+            FireOutcome {
+                fired: false,
+                next_ready_in: 0.5
+            }
+        );
+        assert!(!mock::sent_names().contains(&"MsgUse".to_string()));
+    }
+
+    #[test]
+    fn try_all_runs_every_action_and_returns_the_errors() {
+        mock::on_message(|name, json| match name {
+            "MsgUse" if json["component_id"] == 1 => {
+                MessageType::Error(msg::MsgError { error_code: 3 })
+            }
+            _ => MessageType::Empty(msg::MsgEmpty { value: 0 }),
+        });
+        let result = try_all(vec![
+            Box::new(|| use_component(0, false)),
+            Box::new(|| use_component(1, false)),
+            Box::new(|| use_component(2, false)),
+        ]);
+        assert_eq!(result, Err(vec![MessageError::BadCommand(3)]));
+        let fired: Vec<_> = mock::sent()
+            .iter()
+            .map(|(_, json)| json["component_id"].clone())
+            .collect();
+        assert_eq!(fired, [0, 1, 2]);
+    }
+
+    #[test]
+    fn try_all_succeeds_if_every_action_does() {
+        mock::on_message(|_, _| MessageType::Empty(msg::MsgEmpty { value: 0 }));
+        let result = try_all(vec![
+            Box::new(|| use_component(0, false)),
+            Box::new(|| use_component(1, false)),
+        ]);
+        assert_eq!(result, Ok(()));
+    }
+
+    #[test]
+    fn random_index_is_never_len() {
+        mock::set_random(1.0);
+        assert_eq!(random_index(4), 3);
+        assert_eq!(random_index(1), 0);
+        mock::set_random(0.0);
+        assert_eq!(random_index(4), 0);
+        mock::set_random(0.5);
+        assert_eq!(random_index(4), 2);
+    }
+
+    fn empty() -> MessageType {
+        MessageType::Empty(msg::MsgEmpty { value: 0 })
+    }
+
+    #[test]
+    fn velocity_rejects_non_finite_input_without_sending() {
+        mock::on_message(|_, _| empty());
+        for (x, y, speed) in [
+            (f32::NAN, 0.0, 1.0),
+            (0.0, f32::INFINITY, 1.0),
+            (1.0, 0.0, f32::NAN),
+            (1.0, 0.0, f32::NEG_INFINITY),
+        ] {
+            assert!(matches!(
+                velocity(x, y, speed),
+                Err(MessageError::InvalidArgument(_))
+            ));
+        }
+        assert!(mock::sent().is_empty());
+    }
+
+    #[test]
+    fn velocity_clamps_the_speed() {
+        mock::on_message(|_, _| empty());
+        velocity(1.0, 0.0, 2.5).unwrap();
+        velocity(1.0, 0.0, -1.0).unwrap();
+        velocity(1.0, 0.0, 0.5).unwrap();
+        let speeds: Vec<_> = mock::sent()
+            .iter()
+            .map(|(_, json)| json["speed"].as_f64().unwrap())
+            .collect();
+        assert_eq!(speeds, [1.0, 0.0, 0.5]);
+    }
+
+    #[test]
+    fn strict_mode_rejects_unknown_ids_without_sending() {
+        mock::on_message(|_, _| empty());
+        set_strict(Some(4));
+        assert_eq!(
+            use_component(99, false),
+            Err(MessageError::UnknownComponent(99))
+        );
+        assert_eq!(
+            use_component(-1, false),
+            Err(MessageError::UnknownComponent(-1))
+        );
+        assert!(mock::sent().is_empty());
+        assert_eq!(use_component(3, false), Ok(()));
+        assert_eq!(mock::sent_names(), ["MsgUse"]);
+
+        set_strict(None);
+        assert_eq!(use_component(99, false), Ok(()));
+    }
+
+    #[test]
+    fn strict_mode_passes_other_errors_through() {
+        mock::on_message(|_, _| MessageType::Error(msg::MsgError { error_code: 5 }));
+        set_strict(Some(4));
+        assert_eq!(use_component(1, false), Err(MessageError::BadCommand(5)));
+    }
+
+    /// Answers as a robot whose component 1 is destroyed and whose other
+    /// components have 40 health.
+    fn component_1_destroyed() {
+        mock::on_message(|name, json| match name {
+            "MsgComponentStatusQuery" => MessageType::RComponentStatus(msg::RMsgComponentStatus {
+                health: if json["component_id"] == 1 {
+                    -5.0
+                } else {
+                    40.0
+                },
+                cooldown: 0.0,
+                is_activated: false,
+            }),
+            _ => empty(),
+        });
+    }
+
+    fn fired_ids() -> Vec<i64> {
+        mock::sent()
+            .iter()
+            .filter(|(name, _)| name == "MsgUse")
+            .map(|(_, json)| json["component_id"].as_i64().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn fire_all_skips_destroyed_components() {
+        component_1_destroyed();
+        fire_all(&[0, 1, 2], true).unwrap();
+        assert_eq!(fired_ids(), [0, 2]);
+
+        component_1_destroyed();
+        fire_all(&[0, 1, 2], false).unwrap();
+        assert_eq!(fired_ids(), [0, 1, 2]);
+    }
+
+    #[test]
+    fn total_health_leaves_out_destroyed_components() {
+        component_1_destroyed();
+        assert_eq!(total_health(&[0, 1, 2], true), Ok(80.0));
+        assert_eq!(total_health(&[0, 1, 2], false), Ok(75.0));
+    }
+
+    #[test]
+    fn fire_burst_fires_count_times() {
+        component_cooling(0.0, 0.0);
+        fire_burst(0, 3).unwrap();
+        let names = mock::sent_names();
+        let count = |name: &str| names.iter().filter(|n| *n == name).count();
+        assert_eq!(count("MsgUse"), 3);
+        assert_eq!(count("MsgAwaitAction"), 3);
+        // Every shot waits for the action loop before checking the cooldown.
+        assert_eq!(
+            names[..3],
+            ["MsgAwaitAction", "MsgComponentStatusQuery", "MsgUse"]
+        );
+
+        component_cooling(0.0, 0.0);
+        fire_burst(0, 0).unwrap();
+        assert!(mock::sent().is_empty());
+    }
+
+    #[test]
+    fn dropping_a_stop_guard_stops_the_robot() {
+        mock::on_message(|_, _| empty());
+        {
+            let _guard = StopGuard;
+            velocity(1.0, 0.0, 1.0).unwrap();
+            assert_eq!(mock::sent_names(), ["MsgVelocity"]);
+        }
+        let sent = mock::sent();
+        assert_eq!(sent.len(), 2);
+        assert_eq!(sent[1].0, "MsgVelocity");
+        assert_eq!(sent[1].1["speed"], 0.0);
+    }
+
+    #[test]
+    fn velocity_for_moves_sleeps_and_stops() {
+        mock::on_message(|_, _| empty());
+        velocity_for(0.0, 1.0, 0.8, 0.5).unwrap();
+        assert_eq!(mock::sent_names(), ["MsgVelocity", "MsgVelocity"]);
+        assert_eq!(mock::slept(), [0.5]);
+        let sent = mock::sent();
+        assert_eq!(sent[0].1["y"], 1.0);
+        assert!((sent[0].1["speed"].as_f64().unwrap() - 0.8).abs() < 1e-6);
+        assert_eq!(sent[1].1["speed"], 0.0);
+    }
+
+    #[test]
+    fn velocity_for_does_not_sleep_if_the_velocity_fails() {
+        mock::on_message(|_, _| MessageType::Error(msg::MsgError { error_code: 2 }));
+        assert_eq!(
+            velocity_for(0.0, 1.0, 0.8, 0.5),
+            Err(MessageError::BadCommand(2))
+        );
+        assert!(mock::slept().is_empty());
+        assert_eq!(mock::sent_names(), ["MsgVelocity"]);
+    }
+
+    #[test]
+    fn every_n_ticks_matches_every_nth_tick() {
+        let start = tick_count();
+        let matches: Vec<u64> = (0..9)
+            .map(|_| next_tick())
+            .filter(|_| every_n_ticks(3))
+            .collect();
+        assert_eq!(tick_count(), start + 9);
+        assert_eq!(matches.len(), 3);
+        assert!(matches.iter().all(|tick| tick % 3 == 0));
+
+        assert!(every_n_ticks(1));
+        assert!(!every_n_ticks(0));
+    }
+
+    #[test]
+    fn log_buffer_flushes_all_lines_in_one_call() {
+        mock::on_message(|_, _| empty());
+        let mut log = LogBuffer::new();
+        log.push_line("first");
+        log.push_line("second");
+        assert!(mock::logged().is_empty());
+        log.flush();
+        assert_eq!(mock::logged(), [b"first\nsecond\n".to_vec()]);
+
+        // Nothing is written for an empty buffer.
+        log.flush();
+        assert_eq!(mock::logged().len(), 1);
+    }
+
+    #[test]
+    fn flush_log_writes_the_global_buffer() {
+        mock::on_message(|_, _| empty());
+        buffer_line("a");
+        buffer_line("b");
+        flush_log();
+        flush_log();
+        assert_eq!(mock::logged(), [b"a\nb\n".to_vec()]);
+    }
+
+    #[test]
+    fn await_functions_sleep_for_the_poll_interval() {
+        mock::on_message(|name, _| match name {
+            "MsgComponentStatusQuery" => MessageType::RComponentStatus(msg::RMsgComponentStatus {
+                health: 100.0,
+                cooldown: (0.5 - mock::clock()).max(0.0),
+                is_activated: false,
+            }),
+            _ => empty(),
+        });
+        assert_eq!(poll_interval(), DEFAULT_POLL_INTERVAL);
+        set_poll_interval(0.25).unwrap();
+        await_component(0).unwrap();
+        assert_eq!(mock::slept(), [0.25, 0.25]);
+    }
+
+    #[test]
+    fn set_poll_interval_rejects_invalid_values() {
+        for secs in [0.0, -1.0, f32::NAN, f32::INFINITY] {
+            assert!(matches!(
+                set_poll_interval(secs),
+                Err(MessageError::InvalidArgument(_))
+            ));
+        }
+        assert_eq!(poll_interval(), DEFAULT_POLL_INTERVAL);
+    }
+
+    #[test]
+    fn aim_timeout_scales_with_the_turn() {
+        assert_eq!(aim_timeout_secs(180.0, DEFAULT_ROTATION_RATE, 2.0), 2.0);
+        assert_eq!(aim_timeout_secs(180.0, 90.0, 1.5), 3.0);
+        assert_eq!(
+            aim_timeout_secs(0.0, DEFAULT_ROTATION_RATE, 2.0),
+            poll_interval()
+        );
+    }
+
+    /// Answers as a robot whose rotation is stuck at `angle`, on a clock
+    /// advanced by `rbot::sleep`.
+    fn stuck_at(angle: f32) {
+        mock::on_message(move |name, _| match name {
+            "MsgState" => MessageType::RState(msg::RMsgState {
+                angle,
+                health: 100.0,
+                buffs: Vec::new(),
+            }),
+            "MsgTime" => MessageType::RTime(msg::RMsgTime {
+                timestamp: mock::clock(),
+            }),
+            _ => empty(),
+        });
+    }
+
+    #[test]
+    fn await_aim_bounded_times_out_after_the_bound() {
+        stuck_at(0.0);
+        assert_eq!(
+            await_aim_bounded(0, 180.0, 0.5, DEFAULT_ROTATION_RATE, 2.0),
+            Err(MessageError::Timeout)
+        );
+        assert!((2.0..2.1).contains(&mock::clock()));
+    }
+
+    /// Answers as a robot starting at `angle` that turns instantly to each
+    /// rotation sent.
+    fn turning_from(angle: f32) {
+        let mut angle = angle;
+        mock::on_message(move |name, json| match name {
+            "MsgAngle" => {
+                angle = json["angle"].as_f64().unwrap() as f32;
+                empty()
+            }
+            "MsgState" => MessageType::RState(msg::RMsgState {
+                angle,
+                health: 100.0,
+                buffs: Vec::new(),
+            }),
+            "MsgTime" => MessageType::RTime(msg::RMsgTime {
+                timestamp: mock::clock(),
+            }),
+            _ => empty(),
+        });
+    }
+
+    fn rotations_sent() -> Vec<f64> {
+        mock::sent()
+            .iter()
+            .filter(|(name, _)| name == "MsgAngle")
+            .map(|(_, json)| json["angle"].as_f64().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn aim_directional_turns_the_long_way_clockwise() {
+        turning_from(0.0);
+        aim_directional(0, 90.0, rotations::TurnDirection::Clockwise).unwrap();
+        assert_eq!(rotations_sent(), [225.0, 90.0]);
+
+        turning_from(0.0);
+        aim_directional(0, 270.0, rotations::TurnDirection::Clockwise).unwrap();
+        assert_eq!(rotations_sent(), [270.0]);
+    }
+
+    #[test]
+    fn aim_directional_turns_the_long_way_counter_clockwise() {
+        turning_from(0.0);
+        aim_directional(0, 270.0, rotations::TurnDirection::CounterClockwise).unwrap();
+        assert_eq!(rotations_sent(), [135.0, 270.0]);
+
+        turning_from(0.0);
+        aim_directional(0, 90.0, rotations::TurnDirection::CounterClockwise).unwrap();
+        assert_eq!(rotations_sent(), [90.0]);
+    }
+
+    #[test]
+    fn aim_directional_times_out_if_the_robot_is_stuck() {
+        stuck_at(0.0);
+        assert_eq!(
+            aim_directional(0, 90.0, rotations::TurnDirection::Clockwise),
+            Err(MessageError::Timeout)
+        );
+        // 135 degrees to the waypoint take 0.75 seconds, twice that is allowed.
+        assert!((1.5..1.6).contains(&mock::clock()));
+        assert_eq!(rotations_sent(), [225.0]);
+    }
+
+    #[test]
+    fn component_world_aim_reads_back_the_aimed_angle() {
+        for component_id in [0, 1, 3, 5] {
+            turning_from(200.0);
+            aim(component_id, 45.0).unwrap();
+            let angle = component_world_aim(component_id).unwrap();
+            assert!(
+                rotations::angle_distance(angle, 45.0) < 1e-3,
+                "{component_id}: {angle}"
+            );
+        }
+    }
+
+    /// Aims component 0 from the origin at (10, 0) and returns the rotation sent.
+    fn aimed_from_origin() -> f64 {
+        mock::on_message(|name, _| match name {
+            "MsgGPS" => MessageType::RGPS(msg::RMsgGPS { x: 0.0, y: 0.0 }),
+            _ => empty(),
+        });
+        aim_at_world_point(0, 10.0, 0.0).unwrap();
+        rotations_sent()[0]
+    }
+
+    #[test]
+    fn aim_at_world_point_corrects_for_the_component_offset() {
+        let naive = aimed_from_origin();
+        assert!(rotations::angle_distance(naive as f32, 0.0) < 1e-3);
+
+        // Mounted 2 units to the left of the center, the component has to
+        // turn right to line up with the target.
+        set_component_offset(0, [0.0, 2.0]);
+        let corrected = aimed_from_origin();
+        set_component_offset(0, [0.0, 0.0]);
+        let expected = conversions::xy_to_angle(10.0, -2.0);
+        assert!(rotations::angle_distance(corrected as f32, expected) < 1e-3);
+        assert!(rotations::angle_distance(corrected as f32, naive as f32) > 10.0);
+    }
+
+    #[test]
+    fn await_until_polls_until_the_condition_holds() {
+        mock::on_message(|_, _| empty());
+        let mut polls = 0;
+        await_until(
+            || {
+                polls += 1;
+                Ok(polls == 3)
+            },
+            0.25,
+        )
+        .unwrap();
+        assert_eq!(polls, 3);
+        assert_eq!(mock::slept(), [0.25, 0.25]);
+
+        let result = await_until(|| Err(MessageError::Disconnected), 0.25);
+        assert_eq!(result, Err(MessageError::Disconnected));
+    }
+
+    #[test]
+    fn await_until_timeout_returns_once_the_condition_holds() {
+        stuck_at(0.0);
+        let mut polls = 0;
+        let result = await_until_timeout(
+            || {
+                polls += 1;
+                Ok(polls == 3)
+            },
+            0.25,
+            1.0,
+        );
+        assert_eq!(result, Ok(()));
+        assert_eq!(polls, 3);
+        assert_eq!(mock::slept(), [0.25, 0.25]);
+    }
+
+    #[test]
+    fn await_until_timeout_gives_up_after_the_timeout() {
+        stuck_at(0.0);
+        let result = await_until_timeout(|| Ok(false), 0.25, 1.0);
+        assert_eq!(result, Err(MessageError::Timeout));
+        assert_eq!(mock::clock(), 1.0);
+    }
+
+    #[test]
+    fn report_panic_logs_the_message_after_the_buffered_lines() {
+        mock::on_message(|_, _| empty());
+        buffer_line("tick 7");
+        report_panic(&"panicked at src/main.rs:3:5:\nboom");
+        assert_eq!(
+            mock::logged(),
+            [
+                b"tick 7\n".to_vec(),
+                b"panicked at src/main.rs:3:5:\nboom\n".to_vec()
+            ]
+        );
+    }
+
+    #[test]
+    fn at_rotation_compares_in_the_component_frame() {
+        // Component 3 faces 10° when the robot's rotation is 10° - 270° = 100°.
+        stuck_at(100.0);
+        assert_eq!(at_rotation(3, 10.0, 0.5), Ok(true));
+        assert_eq!(at_rotation(3, 11.0, 0.5), Ok(false));
+        assert_eq!(at_rotation(0, 10.0, 0.5), Ok(false));
+        assert_eq!(at_rotation(0, 100.0, 0.5), Ok(true));
+    }
+
+    /// Answers component status queries with a health of 10 times the id,
+    /// a cooldown of the id and odd components activated.
+    fn components_by_id() {
+        mock::on_message(|name, json| match name {
+            "MsgComponentStatusQuery" => {
+                let id = json["component_id"].as_i64().unwrap();
+                MessageType::RComponentStatus(msg::RMsgComponentStatus {
+                    health: 10.0 * id as f32,
+                    cooldown: id as f32,
+                    is_activated: id % 2 == 1,
+                })
+            }
+            _ => empty(),
+        });
+    }
+
+    #[test]
+    fn component_states_preserves_the_order_of_the_ids() {
+        components_by_id();
+        let states = component_states(&[2, 0, 3]).unwrap();
+        let health: Vec<_> = states.iter().map(|s| s.health).collect();
+        assert_eq!(health, [20.0, 0.0, 30.0]);
+        let queried: Vec<_> = mock::sent()
+            .iter()
+            .map(|(_, json)| json["component_id"].as_i64().unwrap())
+            .collect();
+        assert_eq!(queried, [2, 0, 3]);
+        assert_eq!(component_states(&[]).unwrap().len(), 0);
+    }
+
+    #[test]
+    fn aim_smart_sends_nothing_within_the_deadband() {
+        // Component 1 faces 90° + 90° = 180°.
+        stuck_at(90.0);
+        assert_eq!(aim_smart(1, 181.5, 2.0), Ok(false));
+        assert_eq!(mock::sent_names(), ["MsgState"]);
+        assert!(rotations_sent().is_empty());
+
+        stuck_at(90.0);
+        assert_eq!(aim_smart(1, 190.0, 2.0), Ok(true));
+        assert_eq!(rotations_sent(), [100.0]);
+    }
+
+    fn at_health(health: f32) {
+        mock::on_message(move |name, _| match name {
+            "MsgState" => MessageType::RState(msg::RMsgState {
+                angle: 0.0,
+                health,
+                buffs: Vec::new(),
+            }),
+            _ => empty(),
+        });
+    }
+
+    #[test]
+    fn on_low_health_runs_the_action_only_below_the_threshold() {
+        for (health, expected) in [(29.0, 1), (30.0, 0), (80.0, 0)] {
+            at_health(health);
+            let mut runs = 0;
+            on_low_health(30.0, || {
+                runs += 1;
+                Ok(())
+            })
+            .unwrap();
+            assert_eq!(runs, expected, "{health}");
+        }
+
+        at_health(10.0);
+        let result = on_low_health(30.0, || Err(MessageError::BadCommand(2)));
+        assert_eq!(result, Err(MessageError::BadCommand(2)));
+    }
+
+    /// Answers as a robot that turns instantly to each rotation sent, whose
+    /// components in `cooling` are on cooldown.
+    fn turning_with_cooling(cooling: &'static [i64]) {
+        let mut angle = 90.0;
+        mock::on_message(move |name, json| match name {
+            "MsgAngle" => {
+                angle = json["angle"].as_f64().unwrap() as f32;
+                empty()
+            }
+            "MsgState" => MessageType::RState(msg::RMsgState {
+                angle,
+                health: 100.0,
+                buffs: Vec::new(),
+            }),
+            "MsgComponentStatusQuery" => {
+                let id = json["component_id"].as_i64().unwrap();
+                MessageType::RComponentStatus(msg::RMsgComponentStatus {
+                    health: 100.0,
+                    cooldown: if cooling.contains(&id) { 1.0 } else { 0.0 },
+                    is_activated: false,
+                })
+            }
+            _ => empty(),
+        });
+    }
+
+    #[test]
+    fn assign_and_fire_fires_ready_components_that_line_up() {
+        turning_with_cooling(&[2]);
+        // Aiming component 0 at 0° also lines up component 1 with 90° and
+        // component 2 with 180°, but not component 3 with 45°.
+        assign_and_fire(&[(0, 0.0), (2, 180.0), (1, 90.0), (3, 45.0)]).unwrap();
+        assert_eq!(rotations_sent(), [0.0]);
+        assert_eq!(fired_ids(), [0, 1]);
+    }
+
+    #[test]
+    fn assign_and_fire_does_nothing_without_assignments() {
+        turning_with_cooling(&[]);
+        assign_and_fire(&[]).unwrap();
+        assert!(mock::sent().is_empty());
+    }
+
+    #[test]
+    fn print_bytes_forwards_the_bytes_unchanged() {
+        mock::on_message(|_, _| empty());
+        print_bytes(&[0x7b, 0xff, 0x7d]);
+        print_bytes(&[]);
+        print("héllo");
+        assert_eq!(
+            mock::logged(),
+            [vec![0x7b, 0xff, 0x7d], vec![], "héllo".as_bytes().to_vec()]
+        );
+    }
+
+    /// Answers as a robot starting at `angle` that turns by up to `step`
+    /// degrees towards the last rotation sent each time its state is read.
+    fn turning_by(angle: f32, step: f32) {
+        let (mut angle, mut target) = (angle, angle);
+        mock::on_message(move |name, json| match name {
+            "MsgAngle" => {
+                target = json["angle"].as_f64().unwrap() as f32;
+                empty()
+            }
+            "MsgState" => {
+                let error = rotations::normalize_angle(target - angle + 180.0) - 180.0;
+                angle = rotations::normalize_angle(angle + error.clamp(-step, step));
+                MessageType::RState(msg::RMsgState {
+                    angle,
+                    health: 100.0,
+                    buffs: Vec::new(),
+                })
+            }
+            _ => empty(),
+        });
+    }
+
+    #[test]
+    fn await_aim_returns_once_within_the_slack() {
+        turning_by(0.0, 7.0);
+        await_aim(1, 200.0, 0.5).unwrap();
+        // Component 1 faces 200° at a rotation of 110°.
+        let rotation = state().unwrap().angle;
+        assert!(
+            rotations::angle_distance(rotation, 110.0) <= 0.5,
+            "{rotation}"
+        );
+        assert_eq!(rotations_sent(), [110.0]);
+        let states = mock::sent_names()
+            .iter()
+            .filter(|n| *n == "MsgState")
+            .count();
+        // Sixteen steps of 7° to get within the slack, plus the read above.
+        assert_eq!(states, 17);
+        assert_eq!(mock::slept().len(), 15);
+    }
+
+    #[test]
+    fn is_component_active_reads_the_activation() {
+        components_by_id();
+        assert_eq!(is_component_active(1), Ok(true));
+        assert_eq!(is_component_active(2), Ok(false));
+    }
+
+    #[test]
+    fn is_component_destroyed_checks_for_health_left() {
+        component_1_destroyed();
+        assert_eq!(is_component_destroyed(1), Ok(true));
+        assert_eq!(is_component_destroyed(0), Ok(false));
+
+        // No health left at all counts as destroyed.
+        components_by_id();
+        assert_eq!(is_component_destroyed(0), Ok(true));
+        assert_eq!(is_component_destroyed(3), Ok(false));
+    }
+
+    #[test]
+    fn await_any_component_returns_a_ready_component_at_once() {
+        turning_with_cooling(&[1]);
+        assert_eq!(await_any_component(&[1, 2, 3]), Ok(2));
+        assert!(mock::slept().is_empty());
+
+        assert!(matches!(
+            await_any_component(&[]),
+            Err(MessageError::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn fire_spread_aims_within_the_spread() {
+        let spread = |seed| {
+            turning_with_cooling(&[]);
+            fire_spread(0, 90.0, 20.0, 8, &mut Rng::new(seed)).unwrap();
+            assert_eq!(fired_ids(), [0; 8]);
+            rotations_sent()
+        };
+        let angles = spread(42);
+        assert_eq!(angles.len(), 8);
+        assert!(
+            angles.iter().all(|a| (80.0..=100.0).contains(a)),
+            "{angles:?}"
+        );
+        assert!(angles.iter().any(|a| *a != angles[0]));
+        // The same seed gives the same pattern.
+        assert_eq!(spread(42), angles);
+    }
+}
@@ -1,9 +1,179 @@
 /// Errors that can occur during communication with the game server.
-#[derive(Debug, thiserror::Error)]
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
 pub enum MessageError {
+    /// The game server rejected the command with an error code.
+    ///
+    /// The game server does not publish what its error codes mean, so they
+    /// have no named constants here. Compare codes observed in logs instead.
     #[error("Bad command with error code {0}.")]
     BadCommand(i32),
 
     #[error("Invalid reponse.")]
     InvalidResponse,
+
+    #[error("Timed out waiting for condition.")]
+    Timeout,
+
+    #[error("Invalid argument: {0}.")]
+    InvalidArgument(&'static str),
+
+    #[error("Disconnected from the game server.")]
+    Disconnected,
+
+    #[error("Unknown component id {0}.")]
+    UnknownComponent(i32),
+
+    #[error("{context}: {source}")]
+    WithContext {
+        source: Box<MessageError>,
+        context: &'static str,
+    },
+}
+
+impl MessageError {
+    /// The underlying error, with any context added by `Context::context` removed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rbot::errors::Context;
+    ///
+    /// if let Err(e) = rbot::modules::radar().context("radar") {
+    ///     if e.root() == &rbot::errors::MessageError::Disconnected {
+    ///         return;
+    ///     }
+    /// }
+    /// ```
+    pub fn root(&self) -> &MessageError {
+        match self {
+            MessageError::WithContext { source, .. } => source.root(),
+            error => error,
+        }
+    }
+}
+
+/// Adds context to the error of a `Result`, such as the name of the failed call.
+///
+/// When a strategy makes many calls to the game server, an error such as
+/// `BadCommand(3)` does not say which call failed. Wrapping it with a context
+/// makes the error message name the call.
+///
+/// # Examples
+///
+/// ```
+/// use rbot::errors::Context;
+///
+/// // Prints e.g. "scan for target: Bad command with error code 3."
+/// if let Err(e) = rbot::modules::scan().context("scan for target") {
+///     rbot::println!("{e}");
+/// }
+/// ```
+pub trait Context<T> {
+    /// Wraps the error, if any, in `MessageError::WithContext`.
+    fn context(self, context: &'static str) -> Result<T, MessageError>;
+}
+
+impl<T> Context<T> for Result<T, MessageError> {
+    fn context(self, context: &'static str) -> Result<T, MessageError> {
+        self.map_err(|source| MessageError::WithContext {
+            source: Box::new(source),
+            context,
+        })
+    }
+}
+
+/// Allows `?` to be used on `MessageError` in functions returning `std::io::Error`.
+impl From<MessageError> for std::io::Error {
+    fn from(error: MessageError) -> Self {
+        std::io::Error::other(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn variants_compare_and_clone() {
+        let errors = [
+            MessageError::BadCommand(3),
+            MessageError::InvalidResponse,
+            MessageError::Timeout,
+            MessageError::InvalidArgument("speed must be finite"),
+            MessageError::Disconnected,
+            MessageError::UnknownComponent(99),
+        ];
+        for (i, error) in errors.iter().enumerate() {
+            assert_eq!(error.clone(), *error);
+            for other in &errors[i + 1..] {
+                assert_ne!(error, other);
+            }
+        }
+        assert_ne!(MessageError::BadCommand(3), MessageError::BadCommand(4));
+    }
+
+    #[test]
+    fn variants_display_their_details() {
+        assert_eq!(
+            MessageError::BadCommand(3).to_string(),
+            "Bad command with error code 3."
+        );
+        assert_eq!(
+            MessageError::InvalidArgument("speed must be finite").to_string(),
+            "Invalid argument: speed must be finite."
+        );
+        assert_eq!(
+            MessageError::UnknownComponent(99).to_string(),
+            "Unknown component id 99."
+        );
+        assert_eq!(
+            MessageError::Disconnected.to_string(),
+            "Disconnected from the game server."
+        );
+    }
+
+    #[test]
+    fn context_wraps_and_displays_the_error() {
+        let result: Result<(), MessageError> = Err(MessageError::BadCommand(3));
+        let error = result.context("radar").unwrap_err();
+        assert_eq!(
+            error,
+            MessageError::WithContext {
+                source: Box::new(MessageError::BadCommand(3)),
+                context: "radar",
+            }
+        );
+        assert_eq!(error.to_string(), "radar: Bad command with error code 3.");
+
+        let ok: Result<i32, MessageError> = Ok(1);
+        assert_eq!(ok.context("radar"), Ok(1));
+    }
+
+    #[test]
+    fn root_unwraps_nested_context() {
+        let result: Result<(), MessageError> = Err(MessageError::Timeout);
+        let error = result.context("aim").context("attack").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "attack: aim: Timed out waiting for condition."
+        );
+        assert_eq!(error.root(), &MessageError::Timeout);
+        assert_eq!(MessageError::Timeout.root(), &MessageError::Timeout);
+    }
+
+    #[test]
+    fn converts_into_an_io_error() {
+        fn read() -> std::io::Result<()> {
+            Err(MessageError::Disconnected)?;
+            Ok(())
+        }
+        let error = read().unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::Other);
+        assert_eq!(error.to_string(), "Disconnected from the game server.");
+        let source = error.into_inner().unwrap();
+        assert_eq!(
+            source.downcast_ref::<MessageError>(),
+            Some(&MessageError::Disconnected)
+        );
+    }
 }
@@ -0,0 +1,236 @@
+use crate::core;
+use crate::errors::MessageError;
+use crate::modules;
+use crate::modules::Module;
+use rbot_messages::messages as msg;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+/// A snapshot of the robot for a single logic tick.
+///
+/// Helpers that each query the game server can end up requesting the same
+/// state several times per tick. A `Frame` reads the robot state and time once
+/// when it is created, and memoizes component and module status lookups, so
+/// each of them is requested at most once. Create a new `Frame` at the start
+/// of every tick and pass `&Frame` to the strategy code.
+///
+/// # Examples
+///
+/// ```
+/// loop {
+///     let frame = rbot::frame::Frame::new()?;
+///     for id in 0..4 {
+///         if frame.component_state(id)?.cooldown <= 0.0 {
+///             rbot::use_component(id, false)?;
+///         }
+///     }
+/// }
+/// ```
+pub struct Frame {
+    state: msg::RMsgState,
+    time: f32,
+    components: RefCell<HashMap<i32, Rc<msg::RMsgComponentStatus>>>,
+    modules: RefCell<HashMap<i32, Rc<msg::RMsgModuleStatus>>>,
+}
+
+impl Frame {
+    /// Captures the robot state and time for a new tick.
+    pub fn new() -> Result<Self, MessageError> {
+        Ok(Self {
+            state: core::state()?,
+            time: core::time()?,
+            components: RefCell::new(HashMap::new()),
+            modules: RefCell::new(HashMap::new()),
+        })
+    }
+
+    /// The robot state captured when the frame was created.
+    pub fn state(&self) -> &msg::RMsgState {
+        &self.state
+    }
+
+    /// The timestamp in seconds captured when the frame was created.
+    pub fn time(&self) -> f32 {
+        self.time
+    }
+
+    /// The status of a robot component, requested on first use within this frame.
+    pub fn component_state(
+        &self,
+        component_id: i32,
+    ) -> Result<Rc<msg::RMsgComponentStatus>, MessageError> {
+        if let Some(status) = self.components.borrow().get(&component_id) {
+            return Ok(Rc::clone(status));
+        }
+        let status = Rc::new(core::component_state(component_id)?);
+        self.components
+            .borrow_mut()
+            .insert(component_id, Rc::clone(&status));
+        Ok(status)
+    }
+
+    /// The status of a module, requested on first use within this frame.
+    pub fn module_status(&self, module: Module) -> Result<Rc<msg::RMsgModuleStatus>, MessageError> {
+        let module_id = module as i32;
+        if let Some(status) = self.modules.borrow().get(&module_id) {
+            return Ok(Rc::clone(status));
+        }
+        let status = Rc::new(modules::status(module)?);
+        self.modules
+            .borrow_mut()
+            .insert(module_id, Rc::clone(&status));
+        Ok(status)
+    }
+}
+
+/// The fields of the robot state that changed between two polls of a `StateWatcher`.
+///
+/// Each field holds the new value if it changed, or `None` if it stayed the same.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct StateDelta {
+    pub angle: Option<f32>,
+    pub health: Option<f32>,
+    pub buffs: Option<Vec<String>>,
+}
+
+/// Detects changes in the robot state between polls.
+///
+/// Event-driven strategies often only need to react when something changed,
+/// e.g. when the health dropped or the rotation settled. A `StateWatcher`
+/// remembers the state from the previous poll and reports which fields are
+/// different. The first poll only records the state and reports no change.
+///
+/// # Examples
+///
+/// ```
+/// let mut watcher = rbot::frame::StateWatcher::new();
+/// loop {
+///     if let Some(delta) = watcher.poll()? {
+///         if let Some(health) = delta.health {
+///             rbot::println!("Health changed to {health}");
+///         }
+///     }
+/// }
+/// ```
+#[derive(Default)]
+pub struct StateWatcher {
+    previous: Option<msg::RMsgState>,
+}
+
+impl StateWatcher {
+    /// Creates a watcher that has not seen any state yet.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reads the robot state and compares it to the state from the previous poll.
+    ///
+    /// # Returns
+    ///
+    /// A `StateDelta` with the changed fields, or `None` if nothing changed or
+    /// this is the first poll.
+    pub fn poll(&mut self) -> Result<Option<StateDelta>, MessageError> {
+        let state = core::state()?;
+        let delta = self
+            .previous
+            .as_ref()
+            .and_then(|previous| diff(previous, &state));
+        self.previous = Some(state);
+        Ok(delta)
+    }
+}
+
+/// The fields that differ between two states, or `None` if they are equal.
+fn diff(previous: &msg::RMsgState, current: &msg::RMsgState) -> Option<StateDelta> {
+    let delta = StateDelta {
+        angle: (previous.angle != current.angle).then_some(current.angle),
+        health: (previous.health != current.health).then_some(current.health),
+        buffs: (previous.buffs != current.buffs).then(|| current.buffs.clone()),
+    };
+    (delta != StateDelta::default()).then_some(delta)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hostfn::mock;
+    use rbot_messages::MessageType;
+
+    fn robot() {
+        mock::on_message(|name, _| match name {
+            "MsgState" => MessageType::RState(msg::RMsgState {
+                angle: 90.0,
+                health: 100.0,
+                buffs: Vec::new(),
+            }),
+            "MsgTime" => MessageType::RTime(msg::RMsgTime { timestamp: 12.5 }),
+            "MsgComponentStatusQuery" => MessageType::RComponentStatus(msg::RMsgComponentStatus {
+                health: 50.0,
+                cooldown: 0.0,
+                is_activated: false,
+            }),
+            "MsgModuleStatusQuery" => {
+                MessageType::RModuleStatus(msg::RMsgModuleStatus { cooldown: 1.0 })
+            }
+            _ => MessageType::Empty(msg::MsgEmpty { value: 0 }),
+        });
+    }
+
+    #[test]
+    fn frame_captures_state_and_time_once() {
+        robot();
+        let frame = Frame::new().unwrap();
+        assert_eq!(frame.state().angle, 90.0);
+        assert_eq!(frame.time(), 12.5);
+        assert_eq!(frame.state().health, 100.0);
+        assert_eq!(mock::sent_names(), ["MsgState", "MsgTime"]);
+    }
+
+    #[test]
+    fn frame_requests_each_status_once() {
+        robot();
+        let frame = Frame::new().unwrap();
+        assert_eq!(frame.component_state(0).unwrap().health, 50.0);
+        assert_eq!(frame.component_state(0).unwrap().health, 50.0);
+        frame.component_state(1).unwrap();
+        assert_eq!(frame.module_status(Module::Radar).unwrap().cooldown, 1.0);
+        frame.module_status(Module::Radar).unwrap();
+        assert_eq!(
+            mock::sent_names()[2..],
+            [
+                "MsgComponentStatusQuery",
+                "MsgComponentStatusQuery",
+                "MsgModuleStatusQuery"
+            ]
+        );
+    }
+
+    /// Answers state queries with the given angles in turn, at full health.
+    fn turning(angles: Vec<f32>) {
+        let mut angles = angles.into_iter();
+        mock::on_message(move |name, _| match name {
+            "MsgState" => MessageType::RState(msg::RMsgState {
+                angle: angles.next().unwrap(),
+                health: 100.0,
+                buffs: Vec::new(),
+            }),
+            _ => MessageType::Empty(msg::MsgEmpty { value: 0 }),
+        });
+    }
+
+    #[test]
+    fn state_watcher_reports_changed_fields() {
+        turning(vec![10.0, 10.0, 45.0]);
+        let mut watcher = StateWatcher::new();
+        assert_eq!(watcher.poll(), Ok(None));
+        assert_eq!(watcher.poll(), Ok(None));
+        assert_eq!(
+            watcher.poll(),
+            Ok(Some(StateDelta {
+                angle: Some(45.0),
+                ..StateDelta::default()
+            }))
+        );
+    }
+}
@@ -1,7 +1,16 @@
+use crate::errors::MessageError;
+#[cfg(not(test))]
 use rbot_messages::messages as msg;
 use rbot_messages::{Message, MessageIdentity, MessageType};
+use serde::de::DeserializeOwned;
 use serde::Serialize;
 
+#[cfg(test)]
+pub(crate) mod mock;
+#[cfg(test)]
+pub(crate) use mock::{bot_random, bot_sleep, dbg_log, native_ptr, tcp_send, wasm_ptr};
+
+#[cfg(not(test))]
 extern "C" {
     /// Sends bytes over TCP to the game server and returns a response.
     ///
@@ -23,7 +32,10 @@ extern "C" {
     /// # Returns
     ///
     /// A pointer (`i32`) to an array `[type, size, bytes]` representing the
-    /// response from the game server.
+    /// response from the game server. If the connection to the game server is
+    /// lost, the host returns a null pointer instead. Pointers are wasm32
+    /// addresses, so values that are negative as `i32` are valid addresses
+    /// at or above 2 GiB.
     ///
     /// # Note
     ///
@@ -48,7 +60,10 @@ extern "C" {
     /// effect once the code is deployed.
     pub fn dbg_log(bytes_ptr: i32, size: i32);
 
-    /// Blocks the thread and put it to sleep for x milliseconds
+    /// Blocks the thread and puts it to sleep for the given duration in seconds.
+    ///
+    /// Users are encouraged to use `rbot::sleep`, which ignores non-positive
+    /// durations and enforces a minimum duration.
     pub fn bot_sleep(seconds: f32);
 
     /// Generates a random floating-point value between 0 (inclusive) and 1 (inclusive).
@@ -65,6 +80,18 @@ extern "C" {
     pub fn bot_random() -> f32;
 }
 
+/// Converts a pointer to the address passed to the host functions.
+#[cfg(not(test))]
+pub(crate) fn wasm_ptr<T>(ptr: *const T) -> i32 {
+    ptr as i32
+}
+
+/// Converts an address returned by the host functions to a pointer.
+#[cfg(not(test))]
+pub(crate) fn native_ptr<T>(addr: u32) -> *const T {
+    addr as *const T
+}
+
 /// Sends a message to the game server over TCP using the R-Protocol [TYPE, SIZE, BYTES].
 ///
 /// This function sends a message to the game server using the R-Protocol, which
@@ -74,29 +101,295 @@ extern "C" {
 ///
 /// # Arguments
 ///
-/// * `msg` - A reference to the message (`M`) that implements `Message`, `MessageIdentity`, and `Serialize`.
+/// * `msg` - A reference to the message (`M`) that implements `Message`, `MessageIdentity`,
+///   `Serialize` and `DeserializeOwned`.
 ///
 /// # Returns
 ///
-/// The `MessageType` representing the response type received from the server.
+/// The `MessageType` representing the response type received from the server,
+/// or `MessageError::Disconnected` if the host reports that the connection to
+/// the game server is lost.
 ///
 /// # Note
 ///
 /// The data is currently serialized using JSON encoding but may be subject to change
 /// for faster serialization methods in future implementations.
-pub fn send_message<M: Message + MessageIdentity + Serialize>(msg: &M) -> MessageType {
+#[cfg(not(test))]
+pub fn send_message<M: Message + MessageIdentity + Serialize + DeserializeOwned>(
+    msg: &M,
+) -> Result<MessageType, MessageError> {
+    // Send message byte
+    // [Type Size Bytes]
+    let byte_msg = rbot_messages::serialize_message(msg).unwrap();
+    #[cfg(feature = "debug")]
+    {
+        record_sent(msg);
+        check_round_trip(msg, &byte_msg);
+    }
+    let (typ, bytes) = send_raw(&byte_msg)?;
+    Ok(msg::decode_message(&bytes, typ).unwrap())
+}
+
+/// Answers messages with the handler installed by `mock::on_message`.
+#[cfg(test)]
+pub fn send_message<M: Message + MessageIdentity + Serialize + DeserializeOwned>(
+    msg: &M,
+) -> Result<MessageType, MessageError> {
+    #[cfg(feature = "debug")]
+    {
+        record_sent(msg);
+        check_round_trip(msg, &rbot_messages::serialize_message(msg).unwrap());
+    }
+    mock::respond(msg)
+}
+
+/// Sends an already serialized message to the game server and returns the raw response.
+///
+/// This is an escape hatch for message types the SDK does not wrap yet, e.g.
+/// to experiment with a new server feature. Prefer `send_message` for
+/// everything else.
+///
+/// # Safety contract
+///
+/// The function itself is safe to call, but the game server trusts the
+/// bytes: they must form a complete R-Protocol message, as produced by
+/// `rbot_messages::serialize_message`. The bytes are only borrowed for the
+/// duration of the call. The response payload is allocated by the host with
+/// `alloc_wasm`, and its ownership is transferred to the returned `Vec`, which
+/// frees it when dropped.
+///
+/// # Arguments
+///
+/// * `bytes` - The serialized message to send.
+///
+/// # Returns
+///
+/// The response as `(type, payload)`, where `type` identifies the message type
+/// of the payload, `MessageError::InvalidArgument` if `bytes` is empty, or
+/// `MessageError::Disconnected` if the connection to the game server is lost.
+///
+/// # Examples
+///
+/// ```
+/// let gps = rbot_messages::messages::MsgGPS { value: 0 };
+/// let bytes = rbot_messages::serialize_message(&gps).unwrap();
+/// let (typ, payload) = rbot::hostfn::send_raw(&bytes)?;
+/// let response = rbot_messages::messages::decode_message(&payload, typ).unwrap();
+/// ```
+pub fn send_raw(bytes: &[u8]) -> Result<(i32, Vec<u8>), MessageError> {
+    if bytes.is_empty() {
+        return Err(MessageError::InvalidArgument(
+            "raw message must not be empty",
+        ));
+    }
     unsafe {
-        // Send message byte
-        // [Type Size Bytes]
-        let byte_msg = rbot_messages::serialize_message(msg).unwrap();
-        let result_ptr = tcp_send(
-            std::ptr::addr_of!(byte_msg[0]) as i32,
-            byte_msg.len() as i32,
-        );
+        let result_ptr = tcp_send(wasm_ptr(bytes.as_ptr()), bytes.len() as i32) as u32;
+
+        // A null pointer means the connection is lost.
+        if result_ptr == 0 {
+            return Err(MessageError::Disconnected);
+        }
 
         // Read Result
-        let [typ, size, res_ptr] = *(result_ptr as *const [i32; 3]);
-        let bytes = Vec::from_raw_parts(res_ptr as *mut u8, size as usize, size as usize);
-        msg::decode_message(&bytes, typ).unwrap()
+        let [typ, size, res_ptr] = *native_ptr::<[i32; 3]>(result_ptr);
+        let payload_ptr = native_ptr::<u8>(res_ptr as u32) as *mut u8;
+        let payload = Vec::from_raw_parts(payload_ptr, size as usize, size as usize);
+        Ok((typ, payload))
+    }
+}
+
+#[cfg(feature = "debug")]
+thread_local! {
+    static LAST_SENT_JSON: std::cell::RefCell<Option<String>> = const { std::cell::RefCell::new(None) };
+}
+
+#[cfg(feature = "debug")]
+fn record_sent<M: Serialize>(msg: &M) {
+    let json = serde_json::to_string(msg).ok();
+    LAST_SENT_JSON.with(|last| *last.borrow_mut() = json);
+}
+
+/// The length in bytes of the `[TYPE, SIZE]` header in front of the payload of
+/// a serialized message.
+#[cfg(feature = "debug")]
+const HEADER_LEN: usize = 2 * std::mem::size_of::<i32>();
+
+/// Warns if the payload of the serialized message does not deserialize back
+/// into the same message, which catches message struct drift early.
+#[cfg(feature = "debug")]
+fn check_round_trip<M: Serialize + DeserializeOwned>(msg: &M, byte_msg: &[u8]) {
+    if let Some(warning) = round_trip_warning(msg, byte_msg) {
+        crate::core::print(&format!("Warning: {warning}\n"));
+    }
+}
+
+/// Describes how the payload of the serialized message differs from the
+/// message, or `None` if it deserializes back into the same message.
+#[cfg(feature = "debug")]
+fn round_trip_warning<M: Serialize + DeserializeOwned>(msg: &M, byte_msg: &[u8]) -> Option<String> {
+    let Some(payload) = byte_msg.get(HEADER_LEN..) else {
+        return Some("serialized message is shorter than its header.".into());
+    };
+    let decoded = match serde_json::from_slice::<M>(payload) {
+        Ok(decoded) => decoded,
+        Err(e) => return Some(format!("serialized message does not deserialize: {e}.")),
+    };
+    match (serde_json::to_value(msg), serde_json::to_value(&decoded)) {
+        (Ok(expected), Ok(actual)) if expected == actual => None,
+        (Ok(expected), Ok(actual)) => Some(format!(
+            "serialized message {actual} does not match {expected}."
+        )),
+        _ => Some("message cannot be converted to JSON.".into()),
+    }
+}
+
+/// Returns the JSON form of the most recent message sent with `send_message`.
+///
+/// This is only available with the `debug` feature and is meant for diagnosing
+/// protocol issues, e.g. by logging or replaying the exact messages sent to
+/// the game server. Without the feature, nothing is recorded.
+///
+/// # Returns
+///
+/// The JSON string of the last sent message, or `None` if no message has been
+/// sent yet.
+///
+/// # Examples
+///
+/// ```
+/// rbot::modules::radar()?;
+/// if let Some(json) = rbot::hostfn::last_sent_json() {
+///     rbot::print(&json);
+/// }
+/// ```
+#[cfg(feature = "debug")]
+pub fn last_sent_json() -> Option<String> {
+    LAST_SENT_JSON.with(|last| last.borrow().clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::mock;
+    use crate::errors::MessageError;
+    use rbot_messages::messages as msg;
+    use rbot_messages::MessageType;
+
+    #[test]
+    fn send_message_is_answered_by_the_handler() {
+        mock::on_message(|name, json| match name {
+            "MsgUse" if json["component_id"] == 3 => MessageType::Empty(msg::MsgEmpty { value: 0 }),
+            _ => MessageType::Error(msg::MsgError { error_code: 7 }),
+        });
+        assert!(crate::use_component(3, false).is_ok());
+        assert!(matches!(
+            crate::use_component(4, false),
+            Err(MessageError::BadCommand(7))
+        ));
+        assert_eq!(mock::sent_names(), ["MsgUse", "MsgUse"]);
+        assert_eq!(mock::sent()[1].1["component_id"], 4);
+    }
+
+    #[test]
+    fn send_message_without_handler_is_disconnected() {
+        assert!(matches!(
+            crate::use_component(0, false),
+            Err(MessageError::Disconnected)
+        ));
+    }
+
+    #[test]
+    fn sleep_and_random_use_the_mock_host() {
+        mock::on_message(|_, _| MessageType::Empty(msg::MsgEmpty { value: 0 }));
+        crate::sleep(0.5);
+        crate::sleep(0.0);
+        crate::sleep(0.25);
+        assert_eq!(mock::slept(), [0.5, 0.25]);
+        assert_eq!(mock::clock(), 0.75);
+
+        mock::set_random(0.25);
+        assert_eq!(crate::random_range(0.0, 4.0), 1.0);
+    }
+
+    #[cfg(feature = "debug")]
+    #[test]
+    fn round_trip_accepts_the_serialized_message() {
+        let msg = msg::MsgVelocity {
+            x: 1.0,
+            y: -2.5,
+            speed: 0.5,
+        };
+        let bytes = rbot_messages::serialize_message(&msg).unwrap();
+        assert_eq!(super::round_trip_warning(&msg, &bytes), None);
+    }
+
+    #[cfg(feature = "debug")]
+    #[test]
+    fn round_trip_reports_a_mismatch() {
+        let msg = msg::MsgAngle { angle: 90.0 };
+        let other = rbot_messages::serialize_message(&msg::MsgAngle { angle: 45.0 }).unwrap();
+        assert!(super::round_trip_warning(&msg, &other).is_some());
+        assert!(super::round_trip_warning(&msg, &other[..4]).is_some());
+        // The payload without its header is not a valid message.
+        let mut shifted = other.clone();
+        shifted.drain(..super::HEADER_LEN);
+        assert!(super::round_trip_warning(&msg, &shifted).is_some());
+    }
+
+    #[cfg(feature = "debug")]
+    #[test]
+    fn last_sent_json_records_the_most_recent_message() {
+        mock::on_message(|name, _| match name {
+            "MsgRadar" => MessageType::RRadar(msg::RMsgRadar { x: 1.0, y: 2.0 }),
+            _ => MessageType::Empty(msg::MsgEmpty { value: 0 }),
+        });
+        crate::modules::radar().unwrap();
+        assert_eq!(super::last_sent_json().as_deref(), Some(r#"{"value":0}"#));
+        crate::rotate(12.5).unwrap();
+        assert_eq!(
+            super::last_sent_json().as_deref(),
+            Some(r#"{"angle":12.5}"#)
+        );
+    }
+
+    #[test]
+    fn send_raw_is_disconnected_on_a_null_response() {
+        let received = std::rc::Rc::new(std::cell::RefCell::new(Vec::new()));
+        let handler_received = received.clone();
+        mock::on_raw(move |bytes| {
+            handler_received.borrow_mut().extend_from_slice(bytes);
+            None
+        });
+        assert_eq!(super::send_raw(&[1, 2, 3]), Err(MessageError::Disconnected));
+        assert_eq!(*received.borrow(), [1, 2, 3]);
+    }
+
+    #[test]
+    fn send_raw_round_trips_header_and_payload() {
+        mock::on_raw(|bytes| {
+            // Echo the payload behind the [TYPE, SIZE] header with a new type.
+            let typ = i32::from_le_bytes(bytes[..4].try_into().unwrap());
+            let size = i32::from_le_bytes(bytes[4..8].try_into().unwrap());
+            assert_eq!(size as usize, bytes.len() - 8);
+            Some((typ + 1, bytes[8..].to_vec()))
+        });
+        let mut message = 41_i32.to_le_bytes().to_vec();
+        message.extend(5_i32.to_le_bytes());
+        message.extend(b"hello");
+        assert_eq!(super::send_raw(&message), Ok((42, b"hello".to_vec())));
+    }
+
+    #[test]
+    fn send_raw_accepts_an_empty_response() {
+        mock::on_raw(|_| Some((7, Vec::new())));
+        assert_eq!(super::send_raw(&[0; 8]), Ok((7, Vec::new())));
+    }
+
+    #[test]
+    fn send_raw_rejects_an_empty_message() {
+        mock::on_raw(|_| panic!("nothing should be sent"));
+        assert!(matches!(
+            super::send_raw(&[]),
+            Err(MessageError::InvalidArgument(_))
+        ));
     }
 }
@@ -0,0 +1,193 @@
+//! Test doubles for the host functions.
+//!
+//! Unit tests run natively, where the host functions are not linked. Under
+//! `cfg(test)`, `hostfn` re-exports the functions below in place of the
+//! extern block, and `send_message` hands each message to a handler installed
+//! with `on_message` instead of the game server. Everything is thread local,
+//! so tests running in parallel don't see each other's state.
+//!
+//! This is only a seam for this crate's unit tests. It has no world model:
+//! each test scripts the server's answers itself, and the module is not
+//! compiled outside `cfg(test)`, so other crates can't use it.
+
+use crate::errors::MessageError;
+use rbot_messages::MessageType;
+use serde::Serialize;
+use serde_json::Value;
+use std::cell::RefCell;
+
+type Handler = Box<dyn FnMut(&str, &Value) -> MessageType>;
+type RawHandler = Box<dyn FnMut(&[u8]) -> Option<(i32, Vec<u8>)>>;
+
+#[derive(Default)]
+struct Host {
+    sent: Vec<(String, Value)>,
+    slept: Vec<f32>,
+    logged: Vec<Vec<u8>>,
+    clock: f32,
+    random: f32,
+}
+
+thread_local! {
+    static HOST: RefCell<Host> = RefCell::new(Host::default());
+    static HANDLER: RefCell<Option<Handler>> = const { RefCell::new(None) };
+    static RAW_HANDLER: RefCell<Option<RawHandler>> = const { RefCell::new(None) };
+    static ADDRESSES: RefCell<Vec<usize>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Installs the handler answering messages sent with `send_message` and
+/// resets everything recorded so far.
+///
+/// The handler receives the message type name, e.g. `"MsgUse"`, and the
+/// message as JSON, and returns the response of the game server.
+pub fn on_message<F>(handler: F)
+where
+    F: FnMut(&str, &Value) -> MessageType + 'static,
+{
+    HOST.with(|host| *host.borrow_mut() = Host::default());
+    HANDLER.with(|slot| *slot.borrow_mut() = Some(Box::new(handler)));
+}
+
+/// Installs the handler answering the bytes passed to `tcp_send`, e.g. by
+/// `send_raw`.
+///
+/// The handler returns the response as `(type, payload)`, or `None` to make
+/// `tcp_send` return a null pointer, as the host does when the connection is
+/// lost. Without a handler, `tcp_send` always returns a null pointer.
+pub fn on_raw<F>(handler: F)
+where
+    F: FnMut(&[u8]) -> Option<(i32, Vec<u8>)> + 'static,
+{
+    RAW_HANDLER.with(|slot| *slot.borrow_mut() = Some(Box::new(handler)));
+}
+
+/// Returns the type names of all messages sent since `on_message`.
+pub fn sent_names() -> Vec<String> {
+    HOST.with(|host| {
+        host.borrow()
+            .sent
+            .iter()
+            .map(|(name, _)| name.clone())
+            .collect()
+    })
+}
+
+/// Returns all messages sent since `on_message`, as type name and JSON.
+pub fn sent() -> Vec<(String, Value)> {
+    HOST.with(|host| host.borrow().sent.clone())
+}
+
+/// Returns the durations passed to `bot_sleep`.
+pub fn slept() -> Vec<f32> {
+    HOST.with(|host| host.borrow().slept.clone())
+}
+
+/// Returns the bytes passed to `dbg_log`, one entry per call.
+pub fn logged() -> Vec<Vec<u8>> {
+    HOST.with(|host| host.borrow().logged.clone())
+}
+
+/// Returns the total time slept with `bot_sleep`.
+pub fn clock() -> f32 {
+    HOST.with(|host| host.borrow().clock)
+}
+
+/// Sets the value returned by `bot_random`.
+pub fn set_random(value: f32) {
+    HOST.with(|host| host.borrow_mut().random = value);
+}
+
+/// Answers a message with the installed handler.
+///
+/// Without a handler, the message is treated as sent to a lost connection.
+pub fn respond<M: Serialize>(msg: &M) -> Result<MessageType, MessageError> {
+    let type_name = std::any::type_name::<M>();
+    let name = type_name.rsplit("::").next().unwrap_or(type_name);
+    let json = serde_json::to_value(msg).unwrap();
+    HOST.with(|host| {
+        host.borrow_mut()
+            .sent
+            .push((name.to_string(), json.clone()))
+    });
+    // Take the handler out while it runs, so it may send messages itself.
+    let Some(mut handler) = HANDLER.with(|slot| slot.borrow_mut().take()) else {
+        return Err(MessageError::Disconnected);
+    };
+    let response = handler(name, &json);
+    HANDLER.with(|slot| *slot.borrow_mut() = Some(handler));
+    Ok(response)
+}
+
+/// Mock of `tcp_send`, which answers with the handler installed by `on_raw`.
+///
+/// Like the host, it returns the address of a `[type, size, bytes]` array.
+/// The payload is handed over to the caller, who frees it, and the array is
+/// leaked, as it would be owned by the host.
+///
+/// # Safety
+///
+/// `ptr_void` must be an address from `wasm_ptr` of at least `size` bytes.
+pub unsafe fn tcp_send(ptr_void: i32, size: i32) -> i32 {
+    let bytes = std::slice::from_raw_parts(native_ptr::<u8>(ptr_void as u32), size as usize);
+    let response = RAW_HANDLER.with(|slot| slot.borrow_mut().as_mut().and_then(|h| h(bytes)));
+    let Some((typ, payload)) = response else {
+        return 0;
+    };
+    let payload = Box::leak(payload.into_boxed_slice());
+    let result = Box::leak(Box::new([
+        typ,
+        payload.len() as i32,
+        wasm_ptr(payload.as_ptr()),
+    ]));
+    wasm_ptr(result as *const [i32; 3])
+}
+
+/// Mock of `dbg_log`, which records the bytes.
+///
+/// # Safety
+///
+/// `bytes_ptr` must be an address from `wasm_ptr` of at least `size` bytes.
+pub unsafe fn dbg_log(bytes_ptr: i32, size: i32) {
+    let bytes = std::slice::from_raw_parts(native_ptr::<u8>(bytes_ptr as u32), size as usize);
+    HOST.with(|host| host.borrow_mut().logged.push(bytes.to_vec()));
+}
+
+/// Mock of `bot_sleep`, which records the duration and advances the clock.
+///
+/// # Safety
+///
+/// Safe to call; `unsafe` only to match the host function.
+pub unsafe fn bot_sleep(seconds: f32) {
+    HOST.with(|host| {
+        let mut host = host.borrow_mut();
+        host.slept.push(seconds);
+        host.clock += seconds;
+    });
+}
+
+/// Mock of `bot_random`, which returns the value set with `set_random`.
+///
+/// # Safety
+///
+/// Safe to call; `unsafe` only to match the host function.
+pub unsafe fn bot_random() -> f32 {
+    HOST.with(|host| host.borrow().random)
+}
+
+/// Mock of the conversion of a pointer to a host address.
+///
+/// Native pointers don't fit in an `i32` on 64-bit test hosts, so the pointer
+/// is stored in a table and the address is its position in it, starting at 1
+/// so that it is never null.
+pub fn wasm_ptr<T>(ptr: *const T) -> i32 {
+    ADDRESSES.with(|addresses| {
+        let mut addresses = addresses.borrow_mut();
+        addresses.push(ptr as usize);
+        addresses.len() as i32
+    })
+}
+
+/// Mock of the conversion of a host address back to a pointer, see `wasm_ptr`.
+pub fn native_ptr<T>(addr: u32) -> *const T {
+    ADDRESSES.with(|addresses| addresses.borrow()[addr as usize - 1] as *const T)
+}
@@ -37,14 +37,23 @@
 //! [https://botbeats.net](https://botbeats.net). If you have any questions,
 //! feel free to reach out to us on Discord.
 
+pub mod component;
 pub mod constants;
+pub mod control;
 pub mod conversions;
 pub mod core;
 pub mod errors;
+pub mod frame;
 pub mod hostfn;
 pub mod modules;
+pub mod movement;
+pub mod nav;
+pub mod prelude;
 pub mod print_macros;
 pub mod rotations;
+pub mod store;
+pub mod strategy;
+pub mod targeting;
 pub use crate::core::*;
 pub use rbot_messages::messages;
 
@@ -86,3 +95,46 @@ pub extern "C" fn dummy() {
     core::sleep(100.);
     core::use_component(0, false).unwrap();
 }
+
+/// Reports the version of `rbot` the robot was built with to the host environment.
+///
+/// This allows the runtime to detect robots built with an incompatible
+/// version. The version `major.minor.patch` is encoded as
+/// `major * 1_000_000 + minor * 1_000 + patch`, e.g. 0.2.0 is encoded as 2000.
+///
+/// # Returns
+///
+/// The encoded version of this crate.
+#[no_mangle]
+pub extern "C" fn rbot_version() -> i32 {
+    encode_version(
+        env!("CARGO_PKG_VERSION_MAJOR"),
+        env!("CARGO_PKG_VERSION_MINOR"),
+        env!("CARGO_PKG_VERSION_PATCH"),
+    )
+}
+
+/// Encodes a version as described in `rbot_version`. Parts that are not
+/// numbers are encoded as 0.
+fn encode_version(major: &str, minor: &str, patch: &str) -> i32 {
+    let part = |s: &str| s.parse::<i32>().unwrap_or(0);
+    part(major) * 1_000_000 + part(minor) * 1_000 + part(patch)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rbot_version_encodes_the_package_version() {
+        // Update together with the version in Cargo.toml.
+        assert_eq!(rbot_version(), 2_000);
+    }
+
+    #[test]
+    fn encode_version_combines_the_parts() {
+        assert_eq!(encode_version("0", "2", "0"), 2_000);
+        assert_eq!(encode_version("1", "12", "7"), 1_012_007);
+        assert_eq!(encode_version("3", "x", "1"), 3_000_001);
+    }
+}
@@ -1,11 +1,15 @@
 use crate::await_action;
 use crate::constants;
+use crate::conversions;
 use crate::core;
 use crate::errors::MessageError;
 use crate::hostfn;
+use crate::rotations;
+use crate::targeting;
 use num_derive::FromPrimitive;
 use rbot_messages::messages as msg;
 use rbot_messages::MessageType;
+use strum::IntoEnumIterator;
 use strum_macros::EnumIter;
 
 #[derive(Debug, EnumIter, Clone, Copy, FromPrimitive)]
@@ -21,10 +25,60 @@ pub enum Module {
     GPS,
 }
 
+impl Module {
+    /// Returns the module with the given wire id, or `None` if no module has that id.
+    ///
+    /// This is the inverse of `module as i32`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rbot::modules::Module;
+    /// assert!(matches!(Module::from_id(Module::Radar as i32), Some(Module::Radar)));
+    /// assert!(Module::from_id(-1).is_none());
+    /// ```
+    pub fn from_id(id: i32) -> Option<Module> {
+        Module::iter().find(|module| *module as i32 == id)
+    }
+
+    /// Retrieves the status of the module, see `rbot::modules::status`.
+    ///
+    /// The module specific actions, such as `radar` or `laser`, stay free
+    /// functions in this module.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use rbot::modules::Module;
+    ///
+    /// let radar = Module::Radar;
+    /// radar.await_ready()?;
+    /// let radar_msg = rbot::modules::radar()?;
+    /// ```
+    pub fn status(self) -> Result<msg::RMsgModuleStatus, MessageError> {
+        status(self)
+    }
+
+    /// The remaining cooldown of the module in seconds.
+    pub fn cooldown(self) -> Result<f32, MessageError> {
+        Ok(status(self)?.cooldown)
+    }
+
+    /// Checks whether the module's cooldown is over.
+    pub fn is_ready(self) -> Result<bool, MessageError> {
+        Ok(self.cooldown()? <= 0.0)
+    }
+
+    /// Waits until the module is ready to be used, see `rbot::modules::await_module`.
+    pub fn await_ready(self) -> Result<(), MessageError> {
+        await_module(self)
+    }
+}
+
 /// Macro for handling the incomming message.
 macro_rules! match_message {
     ($msg: expr, $response_type:pat => $response: expr) => {
-        match hostfn::send_message(&$msg) {
+        match hostfn::send_message(&$msg)? {
             MessageType::Error(m) => Err(MessageError::BadCommand(m.error_code)),
             $response_type => $response,
             _ => Err(MessageError::InvalidResponse),
@@ -59,6 +113,32 @@ pub fn status(module: Module) -> Result<msg::RMsgModuleStatus, MessageError> {
     match_message!(msg, MessageType::RModuleStatus(m) => Ok(m))
 }
 
+/// Retrieves the status of several modules at once.
+///
+/// The game server does not yet provide a multi-module query, so this sends
+/// one status request per module. It exists so code refreshing many modules
+/// (e.g. a debug overlay) only needs a single call, and can pick up a batched
+/// request without changes once the protocol offers one.
+///
+/// # Arguments
+///
+/// * `modules` - The modules for which to retrieve the status.
+///
+/// # Returns
+///
+/// A `Result` containing the statuses in the same order as `modules`, or the
+/// first error of type `MessageError` encountered while retrieving them.
+///
+/// # Examples
+///
+/// ```
+/// use rbot::modules::Module;
+/// let statuses = rbot::modules::status_many(&[Module::Radar, Module::Laser])?;
+/// ```
+pub fn status_many(modules: &[Module]) -> Result<Vec<msg::RMsgModuleStatus>, MessageError> {
+    modules.iter().map(|module| status(*module)).collect()
+}
+
 /// Blocks execution until the remaining cooldown of the module expires.
 ///
 /// This function pauses the execution of code until the module's cooldown is
@@ -66,18 +146,42 @@ pub fn status(module: Module) -> Result<msg::RMsgModuleStatus, MessageError> {
 ///
 /// # Examples
 ///
-/// ```
-/// // Wait for the cooldown of the module to expire.
-/// rbot::modules::await_cooldown(Module.Radar);
-/// // Now we can use the module and get a valid response.
-/// let radar_msg = rbot::modules::radar()?;
+/// ```no_run
+/// use rbot::modules::Module;
+///
+/// fn main() -> Result<(), rbot::errors::MessageError> {
+///     // Wait for the cooldown of the module to expire.
+///     rbot::modules::await_module(Module::Radar)?;
+///     // Now we can use the module and get a valid response.
+///     let radar_msg = rbot::modules::radar()?;
+///     Ok(())
+/// }
 /// ```
 pub fn await_module(module: Module) -> Result<(), MessageError> {
     await_action()?;
-    while status(module)?.cooldown > 0.0 {
-        core::sleep(0.01);
-    }
-    Ok(())
+    core::await_until(
+        || Ok(status(module)?.cooldown <= 0.0),
+        core::poll_interval(),
+    )
+}
+
+/// Blocks execution until the remaining cooldown of the module expires.
+///
+/// This is an alias of `await_module`.
+///
+/// # Examples
+///
+/// ```no_run
+/// use rbot::modules::Module;
+///
+/// fn main() -> Result<(), rbot::errors::MessageError> {
+///     rbot::modules::await_cooldown(Module::Radar)?;
+///     let radar_msg = rbot::modules::radar()?;
+///     Ok(())
+/// }
+/// ```
+pub fn await_cooldown(module: Module) -> Result<(), MessageError> {
+    await_module(module)
 }
 
 // ┏━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━
@@ -157,6 +261,65 @@ pub fn radar() -> Result<msg::RMsgRadar, MessageError> {
     match_message!(msg, MessageType::RRadar(m) => Ok(m))
 }
 
+/// Checks whether a radar message reports a position other than (0, 0).
+///
+/// This is a heuristic for whether the radar found an enemy. Neither the
+/// protocol nor `rbot_messages` documents how the radar reports that there is
+/// no contact, and `RMsgRadar` has no flag for it. This function assumes that
+/// a position of exactly (0, 0) means no contact, since an enemy cannot be at
+/// your robot's own position.
+///
+/// # Examples
+///
+/// ```
+/// let radar_msg = rbot::modules::radar()?;
+/// if rbot::modules::radar_is_nonzero(&radar_msg) {
+///     rbot::println!("Enemy at ({}, {})", radar_msg.x, radar_msg.y);
+/// }
+/// ```
+pub fn radar_is_nonzero(radar_msg: &msg::RMsgRadar) -> bool {
+    radar_msg.x != 0.0 || radar_msg.y != 0.0
+}
+
+/// Pulses the radar until it detects an enemy or the timeout expires.
+///
+/// This function repeatedly waits for the radar cooldown and pulses the radar
+/// until it reports a position other than (0, 0), which is taken as a contact
+/// (see `radar_is_nonzero`). It blocks for at least the radar cooldown between
+/// pulses, so the actual wait can exceed `timeout_secs` by up to one cooldown.
+///
+/// # Arguments
+///
+/// * `timeout_secs` - The duration in seconds after which to stop searching.
+///
+/// # Returns
+///
+/// * `Ok(Some(msg::RMsgRadar))` - containing the first radar message with a contact.
+/// * `Ok(None)` - if no contact was found before the timeout expired.
+/// * `Err(MessageError)` - if an error occurs during communication with the game server.
+///
+/// # Examples
+///
+/// ```
+/// match rbot::modules::radar_until_contact(5.0)? {
+///     Some(radar_msg) => rbot::aim(0, rbot::conversions::xy_to_angle(radar_msg.x, radar_msg.y))?,
+///     None => rbot::println!("No enemy found within 5 seconds."),
+/// }
+/// ```
+pub fn radar_until_contact(timeout_secs: f32) -> Result<Option<msg::RMsgRadar>, MessageError> {
+    let start = core::time()?;
+    loop {
+        await_module(Module::Radar)?;
+        let radar_msg = radar()?;
+        if radar_is_nonzero(&radar_msg) {
+            return Ok(Some(radar_msg));
+        }
+        if core::time()? - start >= timeout_secs {
+            return Ok(None);
+        }
+    }
+}
+
 /// Sends a laser scan at a specified angle to detect an object within the
 /// robot's line of sight.
 ///
@@ -205,6 +368,208 @@ pub fn laser(angle: f32) -> Result<msg::RMsgLaser, MessageError> {
     match_message!(msg, MessageType::RLaser(m) => Ok(m))
 }
 
+/// The object hit by a laser scan.
+#[derive(Debug, Clone, PartialEq)]
+pub enum LaserHit {
+    /// The laser did not hit anything.
+    None,
+    /// The laser hit a wall.
+    Wall { distance: f32 },
+    /// The laser hit a sentry.
+    Sentry { distance: f32 },
+    /// The laser hit a bot component of the given `kind` (e.g. Rifle).
+    Component {
+        kind: String,
+        distance: f32,
+        buffs: Vec<String>,
+    },
+    /// The laser hit another object, identified by its `tag` (e.g. Rock).
+    Other { tag: String, distance: f32 },
+}
+
+impl From<msg::RMsgLaser> for LaserHit {
+    fn from(laser_msg: msg::RMsgLaser) -> Self {
+        let distance = laser_msg.distance;
+        match laser_msg.tag.as_str() {
+            "" => LaserHit::None,
+            constants::tag::WALL => LaserHit::Wall { distance },
+            constants::tag::SENTRY => LaserHit::Sentry { distance },
+            constants::tag::COMPONENT => LaserHit::Component {
+                kind: laser_msg.kind,
+                distance,
+                buffs: laser_msg.buffs,
+            },
+            _ => LaserHit::Other {
+                tag: laser_msg.tag,
+                distance,
+            },
+        }
+    }
+}
+
+impl LaserHit {
+    /// The distance to the hit object, or `None` if nothing was hit.
+    pub fn distance(&self) -> Option<f32> {
+        match self {
+            LaserHit::None => None,
+            LaserHit::Wall { distance }
+            | LaserHit::Sentry { distance }
+            | LaserHit::Component { distance, .. }
+            | LaserHit::Other { distance, .. } => Some(*distance),
+        }
+    }
+}
+
+/// Sends a laser scan at a specified angle and returns the hit as a `LaserHit`.
+///
+/// This function behaves like `laser`, but converts the result into a
+/// `LaserHit`, so that the `kind` and `buffs` are only available when a bot
+/// component was hit.
+///
+/// # Arguments
+///
+/// * `angle` - The angle (in degrees) at which to perform the laser scan.
+///
+/// # Returns
+///
+/// A `Result` containing the `LaserHit`, or an error of type `MessageError` if
+/// the scan fails.
+///
+/// # Examples
+///
+/// ```
+/// use rbot::modules::LaserHit;
+///
+/// match rbot::modules::laser_typed(45.0)? {
+///     LaserHit::Component { kind, distance, .. } => rbot::println!("{kind} at {distance}"),
+///     LaserHit::Wall { distance } => rbot::println!("Wall at {distance}"),
+///     _ => {}
+/// }
+/// ```
+pub fn laser_typed(angle: f32) -> Result<LaserHit, MessageError> {
+    Ok(laser(angle)?.into())
+}
+
+/// Measures the distance to the first obstacle in the direction of `angle`.
+///
+/// This fires a laser like `laser_typed`, but only returns the distance of the
+/// hit, for when it does not matter what was hit.
+///
+/// # Arguments
+///
+/// * `angle` - The angle (in degrees) at which to perform the laser scan.
+///
+/// # Returns
+///
+/// A `Result` containing the distance to the hit object, `None` if the laser
+/// did not hit anything, or an error of type `MessageError` if the scan fails.
+///
+/// # Examples
+///
+/// ```
+/// if let Some(distance) = rbot::modules::range_to(90.0)? {
+///     rbot::println!("Obstacle {distance:.1} units above");
+/// }
+/// ```
+pub fn range_to(angle: f32) -> Result<Option<f32>, MessageError> {
+    Ok(laser_typed(angle)?.distance())
+}
+
+/// Fires the laser towards a position relative to the robot.
+///
+/// Relative positions are not rotated with the robot, while the laser angle is
+/// relative to the robot's orientation, so the heading is subtracted first.
+fn laser_towards(x: f32, y: f32) -> Result<msg::RMsgLaser, MessageError> {
+    let heading = core::state()?.angle;
+    laser(rotations::normalize_angle(
+        conversions::xy_to_angle(x, y) - heading,
+    ))
+}
+
+/// Checks whether a target position relative to the robot is in line of sight.
+///
+/// This function fires a laser towards the target and checks what it hits
+/// first. The target is in line of sight if whatever the laser hits is not
+/// closer than the target. A bot component may be up to `BOT_CLUSTER_RADIUS`
+/// closer, as the laser hits the target bot's nearest component rather than
+/// its center, but a component any closer belongs to another bot in the way.
+///
+/// # Arguments
+///
+/// * `target_x` - The x-coordinate of the target relative to the robot's position.
+/// * `target_y` - The y-coordinate of the target relative to the robot's position.
+///
+/// # Returns
+///
+/// A `Result` containing `true` if nothing blocks the line to the target, or an
+/// error of type `MessageError` if the laser scan fails.
+///
+/// # Examples
+///
+/// ```
+/// let radar_msg = rbot::modules::radar()?;
+/// if rbot::modules::has_line_of_sight(radar_msg.x, radar_msg.y)? {
+///     rbot::use_component(0, false)?;
+/// }
+/// ```
+pub fn has_line_of_sight(target_x: f32, target_y: f32) -> Result<bool, MessageError> {
+    let laser_msg = laser_towards(target_x, target_y)?;
+    let distance = target_x.hypot(target_y);
+    if laser_msg.tag == constants::tag::COMPONENT {
+        return Ok(laser_msg.distance >= distance - BOT_CLUSTER_RADIUS);
+    }
+    Ok(laser_msg.tag.is_empty() || laser_msg.distance >= distance)
+}
+
+/// The largest difference between the radar and laser distances for
+/// `confirmed_target` to accept a contact. The radar reports the center of the
+/// enemy bot, while the laser hits its nearest component.
+const CONFIRM_DISTANCE_TOLERANCE: f32 = BOT_CLUSTER_RADIUS;
+
+/// Finds the enemy with the radar and confirms it with the laser.
+///
+/// The radar contact can be occluded or stale. This function fires the laser
+/// towards the radar contact and only returns it if the laser hits a bot
+/// component at roughly the radar distance. A wall or other object in front of
+/// the enemy rejects the contact. Both the Radar and the Laser must be ready.
+///
+/// # Returns
+///
+/// * `Ok(Some(msg::RMsgScanObject))` - the radar position of the enemy, with the
+///   `kind` and `buffs` of the component hit by the laser.
+/// * `Ok(None)` - if the radar has no contact or the laser does not confirm it.
+/// * `Err(MessageError)` - if an error occurs during the radar or laser scan.
+///
+/// # Examples
+///
+/// ```
+/// if let Some(target) = rbot::modules::confirmed_target()? {
+///     let angle = rbot::conversions::xy_to_angle(target.x, target.y);
+///     rbot::await_aim(0, angle, 1.0)?;
+///     rbot::use_component(0, false)?;
+/// }
+/// ```
+pub fn confirmed_target() -> Result<Option<msg::RMsgScanObject>, MessageError> {
+    let radar_msg = radar()?;
+    if !radar_is_nonzero(&radar_msg) {
+        return Ok(None);
+    }
+    let laser_msg = laser_towards(radar_msg.x, radar_msg.y)?;
+    let radar_distance = radar_msg.x.hypot(radar_msg.y);
+    if laser_msg.tag != constants::tag::COMPONENT
+        || (laser_msg.distance - radar_distance).abs() > CONFIRM_DISTANCE_TOLERANCE
+    {
+        return Ok(None);
+    }
+    Ok(Some(msg::RMsgScanObject {
+        x: radar_msg.x,
+        y: radar_msg.y,
+        tag: constants::tag::BOT.into(),
+        kind: laser_msg.kind,
+        buffs: laser_msg.buffs,
+    }))
+}
+
 /// Activates a force field that grants temporary invincibility to the robot.
 ///
 /// This function activates a force field that provides temporary invincibility to the robot,
@@ -246,6 +611,47 @@ pub fn mine() -> Result<msg::MsgEmpty, MessageError> {
     match_message!(msg, MessageType::Empty(m) => Ok(m))
 }
 
+/// Predicts whether the robot will drive over its own mine after it arms.
+///
+/// A mine is dropped at the robot's current position and arms after
+/// `arm_delay` seconds. This function projects the robot's position with a
+/// constant velocity and checks whether it is within `radius` of the drop
+/// point at any time between the mine arming and `seconds_ahead`.
+///
+/// # Arguments
+///
+/// * `my_velocity` - The robot's velocity `[x, y]` in units per second.
+/// * `seconds_ahead` - How far ahead in seconds to predict.
+/// * `arm_delay` - The time in seconds after which the mine arms.
+/// * `radius` - The distance from the mine at which it is triggered.
+///
+/// # Returns
+///
+/// `true` if the robot is predicted to be within `radius` of the mine while it
+/// is armed, otherwise `false`.
+///
+/// # Examples
+///
+/// ```
+/// if !rbot::modules::mine_self_risk([4.0, 0.0], 3.0, 1.0, 1.5) {
+///     rbot::modules::mine()?;
+/// }
+/// ```
+pub fn mine_self_risk(
+    my_velocity: [f32; 2],
+    seconds_ahead: f32,
+    arm_delay: f32,
+    radius: f32,
+) -> bool {
+    if seconds_ahead < arm_delay {
+        return false;
+    }
+    // With a constant velocity the robot only moves away from the drop point,
+    // so it is closest to the mine at the moment the mine arms.
+    let [vx, vy] = my_velocity;
+    (vx * arm_delay).hypot(vy * arm_delay) < radius
+}
+
 /// Repairs the specified robot component, restoring it to a significantly
 /// healthier state.
 ///
@@ -272,6 +678,89 @@ pub fn repair(component_id: i32) -> Result<msg::RMsgRepair, MessageError> {
     match_message!(msg, MessageType::RRepair(m) => Ok(m))
 }
 
+/// Repairs several components in turn, waiting for the Repair cooldown before each.
+///
+/// This function blocks until every component has been repaired.
+///
+/// # Arguments
+///
+/// * `component_ids` - The identifiers of the components to repair.
+/// * `skip_destroyed` - Whether to skip destroyed components instead of
+///   waiting for the cooldown and repairing them.
+///
+/// # Returns
+///
+/// Returns `Ok(())` once every repair has been sent, or an error of type
+/// `MessageError` if there was a problem during communication with the game
+/// server.
+///
+/// # Examples
+///
+/// ```
+/// rbot::modules::repair_all(&[0, 1, 2, 3], true)?;
+/// ```
+pub fn repair_all(component_ids: &[i32], skip_destroyed: bool) -> Result<(), MessageError> {
+    for id in component_ids {
+        if skip_destroyed && core::is_component_destroyed(*id)? {
+            continue;
+        }
+        await_module(Module::Repair)?;
+        repair(*id)?;
+    }
+    Ok(())
+}
+
+/// Repairs the most damaged component if the Repair module is ready.
+///
+/// Call this every tick for a maintenance behavior. If the Repair module is
+/// on cooldown, nothing else is queried. Otherwise the component with the
+/// lowest health below `threshold` is repaired, and the function waits for
+/// the next action loop, in which the repair is performed, so the next call
+/// sees the new cooldown.
+///
+/// # Arguments
+///
+/// * `component_ids` - The identifiers of the components to keep repaired.
+/// * `threshold` - Components with at least this much health are not repaired.
+///
+/// # Returns
+///
+/// The identifier of the repaired component, `None` if the Repair module is
+/// not ready or no component is below `threshold`, or an error of type
+/// `MessageError` if there was a problem during communication with the game
+/// server.
+///
+/// # Examples
+///
+/// ```
+/// loop {
+///     if let Some(id) = rbot::modules::auto_repair_tick(&[0, 1, 2, 3], 50.0)? {
+///         rbot::println!("Repaired component {id}");
+///     }
+/// }
+/// ```
+pub fn auto_repair_tick(
+    component_ids: &[i32],
+    threshold: f32,
+) -> Result<Option<i32>, MessageError> {
+    if !Module::Repair.is_ready()? {
+        return Ok(None);
+    }
+    let states = core::component_states(component_ids)?;
+    let weakest = component_ids
+        .iter()
+        .zip(&states)
+        .filter(|(_, state)| state.health < threshold)
+        .min_by(|(_, a), (_, b)| a.health.total_cmp(&b.health))
+        .map(|(id, _)| *id);
+
+    if let Some(component_id) = weakest {
+        repair(component_id)?;
+        await_action()?;
+    }
+    Ok(weakest)
+}
+
 /// Activates a thruster to swiftly move the robot a short distance in the
 /// specified global angle.
 ///
@@ -303,6 +792,63 @@ pub fn thrust(angle: f32) -> Result<msg::MsgEmpty, MessageError> {
     match_message!(msg, MessageType::Empty(m) => Ok(m))
 }
 
+/// Thrusts the robot `pulses` times in the same direction for sustained travel.
+///
+/// Before each pulse, the function waits for the Thruster cooldown to expire,
+/// so it blocks until the last pulse has been fired. This is the thruster
+/// analogue of `rbot::fire_burst`.
+///
+/// # Arguments
+///
+/// * `angle` - The global angle (in degrees) at which to move the robot.
+/// * `pulses` - The number of times to activate the thruster.
+///
+/// # Returns
+///
+/// Returns `Ok(())` once every pulse has been fired, or an error of type
+/// `MessageError` if there was a problem during communication with the game
+/// server.
+///
+/// # Examples
+///
+/// ```
+/// // Travel far to the right.
+/// rbot::modules::thrust_sustained(0.0, 3)?;
+/// ```
+pub fn thrust_sustained(angle: f32, pulses: u32) -> Result<(), MessageError> {
+    for _ in 0..pulses {
+        await_module(Module::Thruster)?;
+        thrust(angle)?;
+    }
+    Ok(())
+}
+
+/// Activates a thruster to swiftly move the robot towards a point relative to it.
+///
+/// This function converts the relative position (`x`, `y`) to a global angle
+/// and calls `thrust` with it.
+///
+/// # Arguments
+///
+/// * `x` - The x-coordinate of the point relative to the robot's position.
+/// * `y` - The y-coordinate of the point relative to the robot's position.
+///
+/// # Returns
+///
+/// A `Result` containing `msg::MsgEmpty` indicating successful activation of the thruster,
+/// or an error of type `MessageError` if the activation fails.
+///
+/// # Examples
+///
+/// ```
+/// // Thrust towards the enemy found by the radar.
+/// let radar_msg = rbot::modules::radar()?;
+/// let result = rbot::modules::thrust_towards(radar_msg.x, radar_msg.y);
+/// ```
+pub fn thrust_towards(x: f32, y: f32) -> Result<msg::MsgEmpty, MessageError> {
+    thrust(conversions::xy_to_angle_360(x, y))
+}
+
 /// Initiates a 360-degree scan to detect nearby objects around the robot within a specified range.
 ///
 /// This function performs a full 360-degree scan around the robot to detect all nearby objects
@@ -346,17 +892,22 @@ pub fn scan() -> Result<msg::RMsgScan, MessageError> {
     match_message!(msg, MessageType::RScan(m) => Ok(m))
 }
 
-/// Scans for the average position of the components of an enemy bot, if any are found.
+/// Maximum distance between two components for them to belong to the same bot.
+const BOT_CLUSTER_RADIUS: f32 = 3.0;
+
+/// Scans for the position of the nearest enemy bot, if any are found.
 ///
-/// This function is useful for locating the center of an enemy bot. It attempts to find
-/// the position of the motherboard first. If the motherboard is not found, it calculates
-/// and returns the average position of the bot's components. If no components are found,
-/// it returns `None`.
+/// This function is useful for locating the center of an enemy bot. The
+/// detected components are first grouped into bots by their distance to each
+/// other, and the bot closest to your robot is selected. It then attempts to
+/// find the position of that bot's motherboard. If the motherboard is not
+/// found, it calculates and returns the average position of the bot's
+/// components. If no components are found, it returns `None`.
 ///
 /// # Returns
 ///
 /// * `Ok(Some(msg::RMsgScanObject))` - containing the position of the motherboard if found,
-///   or the average position of the components.
+///   or the average position of the components of the nearest bot.
 /// * `Ok(None)` - if no components are found.
 /// * `Err(MessageError)` - if an error occurs during the scan.
 ///
@@ -370,43 +921,223 @@ pub fn scan() -> Result<msg::RMsgScan, MessageError> {
 /// }
 /// ```
 pub fn scan_for_bot() -> Result<Option<msg::RMsgScanObject>, MessageError> {
+    let scan_msg = scan()?;
+    let nearest = nearest_bot(&scan_msg.objects);
+
+    Ok(nearest.map(|bot| summarize_bot(&bot)))
+}
+
+/// Counts the distinct bots among scanned objects.
+///
+/// The scanner reports every component of a bot as a separate object. This
+/// function groups the bot components by their distance to each other, like
+/// `scan_for_bot` does, and counts the groups. Two components exactly
+/// `cluster_radius` apart belong to the same bot. Objects that are not bot
+/// components, such as walls, are ignored.
+///
+/// # Arguments
+///
+/// * `objects` - The scan objects to count the bots of, e.g. from `scan`.
+/// * `cluster_radius` - The largest distance between two components of the same bot.
+///
+/// # Returns
+///
+/// The number of distinct bots.
+///
+/// # Examples
+///
+/// ```
+/// let scan_msg = rbot::modules::scan()?;
+/// let bots = rbot::modules::count_bots(&scan_msg.objects, 3.0);
+/// rbot::println!("{bots} bots in range");
+/// ```
+pub fn count_bots(objects: &[msg::RMsgScanObject], cluster_radius: f32) -> usize {
+    let components: Vec<_> = objects
+        .iter()
+        .filter(|o| o.tag == constants::tag::COMPONENT)
+        .collect();
+    cluster_by_distance(&components, cluster_radius).len()
+}
+
+/// A summary of an enemy bot detected by the scanner.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BotSummary {
+    /// The average position of the bot's components relative to your robot.
+    pub center: [f32; 2],
+    /// The position of the bot's motherboard relative to your robot, if detected.
+    pub motherboard: Option<[f32; 2]>,
+    /// The number of detected components of the bot.
+    pub component_count: usize,
+    /// The largest distance between two of the bot's components.
+    pub spread: f32,
+}
+
+/// Scans for the nearest enemy bot and summarizes its position and shape.
+///
+/// This function selects the nearest bot like `scan_for_bot`, but instead of
+/// only returning one position it reports both the average position of the
+/// components and the motherboard position, along with how many components
+/// were detected and how spread out they are. This is enough to choose between
+/// single-target and area weapons in one call.
+///
+/// # Returns
+///
+/// * `Ok(Some(BotSummary))` - containing the summary of the nearest bot.
+/// * `Ok(None)` - if no components are found.
+/// * `Err(MessageError)` - if an error occurs during the scan.
+///
+/// # Examples
+///
+/// ```
+/// if let Some(bot) = rbot::modules::scan_bot_detailed()? {
+///     let [x, y] = bot.motherboard.unwrap_or(bot.center);
+///     rbot::aim(0, rbot::conversions::xy_to_angle(x, y))?;
+/// }
+/// ```
+pub fn scan_bot_detailed() -> Result<Option<BotSummary>, MessageError> {
+    let scan_msg = scan()?;
+    let nearest = nearest_bot(&scan_msg.objects);
+
+    Ok(nearest.map(|bot| {
+        let count = bot.len() as f32;
+        BotSummary {
+            center: [
+                bot.iter().map(|c| c.x).sum::<f32>() / count,
+                bot.iter().map(|c| c.y).sum::<f32>() / count,
+            ],
+            motherboard: bot
+                .iter()
+                .find(|c| c.kind == constants::kind::MOTHERBOARD)
+                .map(|c| [c.x, c.y]),
+            component_count: bot.len(),
+            spread: targeting::spread(&bot),
+        }
+    }))
+}
+
+/// Scans for enemy bots and picks the nearest one in line of sight.
+///
+/// This is the "just give me something to shoot" helper. The detected
+/// components are grouped into bots like in `scan_for_bot`, and the bots are
+/// checked from nearest to furthest with `has_line_of_sight`. The scan does not
+/// report the health of components, so bots are only ranked by distance.
+///
+/// Each line of sight check fires the laser, so this function waits for the
+/// Laser cooldown before every check and can block for several cooldowns when
+/// the nearest bots are hidden.
+///
+/// # Returns
+///
+/// * `Ok(Some(msg::RMsgScanObject))` - the position of the chosen bot's motherboard
+///   if found, or the average position of its components.
+/// * `Ok(None)` - if no bot is found or none is in line of sight.
+/// * `Err(MessageError)` - if an error occurs during the scan or a laser check.
+///
+/// # Examples
+///
+/// ```
+/// if let Some(target) = rbot::modules::best_target()? {
+///     let angle = rbot::conversions::xy_to_angle(target.x, target.y);
+///     rbot::await_aim(0, angle, 1.0)?;
+///     rbot::use_component(0, false)?;
+/// }
+/// ```
+pub fn best_target() -> Result<Option<msg::RMsgScanObject>, MessageError> {
     let scan_msg = scan()?;
     let components: Vec<_> = scan_msg
         .objects
-        .into_iter()
-        .filter(|o| o.tag == crate::constants::tag::COMPONENT)
+        .iter()
+        .filter(|o| o.tag == constants::tag::COMPONENT)
         .collect();
 
-    if components.len() == 0 {
-        return Ok(None);
+    let mut bots = cluster_by_distance(&components, BOT_CLUSTER_RADIUS);
+    bots.sort_by(|a, b| nearest_distance(a).total_cmp(&nearest_distance(b)));
+    for bot in bots {
+        let target = summarize_bot(&bot);
+        await_module(Module::Laser)?;
+        if has_line_of_sight(target.x, target.y)? {
+            return Ok(Some(target));
+        }
     }
+    Ok(None)
+}
+
+/// Groups the bot components among the scan objects into bots and returns the
+/// components of the bot nearest to the robot.
+fn nearest_bot(objects: &[msg::RMsgScanObject]) -> Option<Vec<&msg::RMsgScanObject>> {
+    let components: Vec<_> = objects
+        .iter()
+        .filter(|o| o.tag == constants::tag::COMPONENT)
+        .collect();
 
+    cluster_by_distance(&components, BOT_CLUSTER_RADIUS)
+        .into_iter()
+        .min_by(|a, b| nearest_distance(a).total_cmp(&nearest_distance(b)))
+}
+
+/// Groups objects so that every object is within `radius` of at least one
+/// other object in its group.
+pub(crate) fn cluster_by_distance<'a>(
+    objects: &[&'a msg::RMsgScanObject],
+    radius: f32,
+) -> Vec<Vec<&'a msg::RMsgScanObject>> {
+    let mut remaining = objects.to_vec();
+    let mut clusters = vec![];
+
+    while let Some(first) = remaining.pop() {
+        let mut cluster = vec![first];
+        let mut i = 0;
+        while i < cluster.len() {
+            let (x, y) = (cluster[i].x, cluster[i].y);
+            let (near, far): (Vec<_>, Vec<_>) = remaining
+                .into_iter()
+                .partition(|o| (o.x - x).hypot(o.y - y) <= radius);
+            remaining = far;
+            cluster.extend(near);
+            i += 1;
+        }
+        clusters.push(cluster);
+    }
+    clusters
+}
+
+/// Distance from the robot to the closest object in the cluster.
+fn nearest_distance(cluster: &[&msg::RMsgScanObject]) -> f32 {
+    cluster
+        .iter()
+        .map(|o| o.x.hypot(o.y))
+        .fold(f32::INFINITY, f32::min)
+}
+
+/// Summarizes the components of a single bot into one scan object, located at
+/// the motherboard if found or else at the average position of the components.
+pub(crate) fn summarize_bot(components: &[&msg::RMsgScanObject]) -> msg::RMsgScanObject {
     let motherboard = components
         .iter()
         .find(|c| c.kind == constants::kind::MOTHERBOARD);
 
     // If the motherboard is found, return its position and information
     if let Some(motherboard) = motherboard {
-        return Ok(Some(msg::RMsgScanObject {
+        return msg::RMsgScanObject {
             x: motherboard.x,
             y: motherboard.y,
             tag: constants::tag::BOT.into(),
             kind: "".into(),
             buffs: motherboard.buffs.to_owned(),
-        }));
+        };
     }
 
     // Calculate the average position of the components if the motherboard is not found
     let x: f32 = components.iter().map(|c| c.x).sum::<f32>() / components.len() as f32;
     let y: f32 = components.iter().map(|c| c.y).sum::<f32>() / components.len() as f32;
 
-    Ok(Some(msg::RMsgScanObject {
+    msg::RMsgScanObject {
         x,
         y,
         tag: constants::tag::BOT.into(),
         kind: "".into(),
         buffs: vec![],
-    }))
+    }
 }
 
 /// Retrieves the absolute position (`x`, `y`) of the robot from the center of the map using GPS.
@@ -428,3 +1159,722 @@ pub fn gps() -> Result<msg::RMsgGPS, MessageError> {
     let msg = msg::MsgGPS { value: 0 };
     match_message!(msg, MessageType::RGPS(m) => Ok(m))
 }
+
+/// A quadrant of the map, as seen from its center.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Quadrant {
+    NorthEast,
+    NorthWest,
+    SouthEast,
+    SouthWest,
+}
+
+impl Quadrant {
+    /// The quadrant containing the absolute position (`x`, `y`).
+    ///
+    /// Points on an axis count towards the north or east side.
+    pub fn of(x: f32, y: f32) -> Self {
+        match (x >= 0.0, y >= 0.0) {
+            (true, true) => Quadrant::NorthEast,
+            (false, true) => Quadrant::NorthWest,
+            (true, false) => Quadrant::SouthEast,
+            (false, false) => Quadrant::SouthWest,
+        }
+    }
+}
+
+/// The robot's absolute position along with values derived from it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GpsInfo {
+    /// The x-coordinate from the center of the map.
+    pub x: f32,
+    /// The y-coordinate from the center of the map.
+    pub y: f32,
+    /// The distance from the center of the map.
+    pub distance_from_center: f32,
+    /// The quadrant of the map the robot is in.
+    pub quadrant: Quadrant,
+}
+
+/// Retrieves the robot's absolute position using GPS, along with its distance
+/// from the center of the map and the quadrant it is in.
+///
+/// # Returns
+///
+/// A `Result` containing the `GpsInfo`, or an error of type `MessageError` if
+/// the GPS retrieval fails.
+///
+/// # Examples
+///
+/// ```
+/// // Stay away from the center of the map.
+/// let gps = rbot::modules::gps_detailed()?;
+/// if gps.distance_from_center < 10.0 {
+///     rbot::velocity(gps.x, gps.y, 1.0)?;
+/// }
+/// ```
+pub fn gps_detailed() -> Result<GpsInfo, MessageError> {
+    let gps_msg = gps()?;
+    Ok(GpsInfo {
+        x: gps_msg.x,
+        y: gps_msg.y,
+        distance_from_center: gps_msg.x.hypot(gps_msg.y),
+        quadrant: Quadrant::of(gps_msg.x, gps_msg.y),
+    })
+}
+
+/// Converts an absolute position on the map to a position relative to the robot.
+///
+/// Radar and scan report positions relative to the robot, while GPS reports
+/// absolute positions from the center of the map. This function reads the
+/// robot's position with `gps` and subtracts it, so a target computed in map
+/// space (e.g. a chokepoint) can be passed to `xy_to_angle` or `thrust_towards`.
+///
+/// # Arguments
+///
+/// * `target_abs` - The absolute position `[x, y]` from the center of the map.
+///
+/// # Returns
+///
+/// The position `[x, y]` relative to the robot, or an error of type
+/// `MessageError` if the GPS retrieval fails.
+///
+/// # Examples
+///
+/// ```
+/// // Thrust towards the center of the map.
+/// let [x, y] = rbot::modules::absolute_to_relative([0.0, 0.0])?;
+/// rbot::modules::thrust_towards(x, y)?;
+/// ```
+pub fn absolute_to_relative(target_abs: [f32; 2]) -> Result<[f32; 2], MessageError> {
+    let position = gps()?;
+    Ok([target_abs[0] - position.x, target_abs[1] - position.y])
+}
+
+/// Converts a position relative to the robot to an absolute position on the map.
+///
+/// This is the inverse of `absolute_to_relative`, e.g. to remember where the
+/// radar last saw the enemy while the robot keeps moving.
+///
+/// # Arguments
+///
+/// * `target_rel` - The position `[x, y]` relative to the robot.
+///
+/// # Returns
+///
+/// The absolute position `[x, y]` from the center of the map, or an error of
+/// type `MessageError` if the GPS retrieval fails.
+///
+/// # Examples
+///
+/// ```
+/// let radar_msg = rbot::modules::radar()?;
+/// let enemy = rbot::modules::relative_to_absolute([radar_msg.x, radar_msg.y])?;
+/// ```
+pub fn relative_to_absolute(target_rel: [f32; 2]) -> Result<[f32; 2], MessageError> {
+    let position = gps()?;
+    Ok([target_rel[0] + position.x, target_rel[1] + position.y])
+}
+
+/// Caches radar and scan results until the module is ready again.
+///
+/// Querying the Radar or Scanner while it is on cooldown does not return new
+/// information. `SensorCache` remembers the last result together with the time
+/// at which the module's cooldown expires, and returns the cached result until
+/// then without sending a new request.
+///
+/// # Examples
+///
+/// ```
+/// let mut sensors = rbot::modules::SensorCache::new();
+/// loop {
+///     let radar_msg = sensors.radar_cached()?;
+///     let angle = rbot::conversions::xy_to_angle(radar_msg.x, radar_msg.y);
+///     rbot::aim(0, angle)?;
+/// }
+/// ```
+#[derive(Default)]
+pub struct SensorCache {
+    radar: Option<(msg::RMsgRadar, f32)>,
+    scan: Option<(msg::RMsgScan, f32)>,
+}
+
+impl SensorCache {
+    /// Creates an empty cache.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the last radar result, or calls `radar` if the Radar is ready again.
+    pub fn radar_cached(&mut self) -> Result<&msg::RMsgRadar, MessageError> {
+        cached(&mut self.radar, Module::Radar, radar)
+    }
+
+    /// Returns the last scan result, or calls `scan` if the Scanner is ready again.
+    pub fn scan_cached(&mut self) -> Result<&msg::RMsgScan, MessageError> {
+        cached(&mut self.scan, Module::Scanner, scan)
+    }
+
+    /// Discards the cached results, so the next calls query the modules again.
+    pub fn invalidate(&mut self) {
+        self.radar = None;
+        self.scan = None;
+    }
+}
+
+/// Returns the cached value in `slot` if it has not expired, otherwise fetches
+/// a new one and stores it until the module's cooldown expires.
+fn cached<T>(
+    slot: &mut Option<(T, f32)>,
+    module: Module,
+    fetch: fn() -> Result<T, MessageError>,
+) -> Result<&T, MessageError> {
+    let now = core::time()?;
+    if !matches!(slot, Some((_, expiry)) if now < *expiry) {
+        let value = fetch()?;
+        // The cooldown only starts once the module is used in the next action loop.
+        await_action()?;
+        let expiry = now + status(module)?.cooldown;
+        *slot = Some((value, expiry));
+    }
+    Ok(&slot.as_ref().unwrap().0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hostfn::mock;
+
+    fn laser_hit(tag: &str, distance: f32) -> MessageType {
+        MessageType::RLaser(msg::RMsgLaser {
+            tag: tag.to_string(),
+            kind: String::new(),
+            distance,
+            angle: 0.0,
+            buffs: Vec::new(),
+        })
+    }
+
+    /// Answers as a robot facing `heading` with a wall at distance 2 in every
+    /// direction except straight ahead, where `ahead` is hit. The radar
+    /// reports a contact at (0, 5).
+    fn facing(heading: f32, ahead: MessageType) {
+        let mut ahead = Some(ahead);
+        mock::on_message(move |name, json| match name {
+            "MsgRadar" => MessageType::RRadar(msg::RMsgRadar { x: 0.0, y: 5.0 }),
+            "MsgState" => MessageType::RState(msg::RMsgState {
+                angle: heading,
+                health: 100.0,
+                buffs: Vec::new(),
+            }),
+            "MsgLaser" if json["angle"] == 0.0 => ahead.take().unwrap(),
+            "MsgLaser" => laser_hit(constants::tag::WALL, 2.0),
+            _ => MessageType::Empty(msg::MsgEmpty { value: 0 }),
+        });
+    }
+
+    #[test]
+    fn has_line_of_sight_aims_relative_to_the_heading() {
+        facing(90.0, laser_hit(constants::tag::COMPONENT, 5.0));
+        assert_eq!(has_line_of_sight(0.0, 5.0), Ok(true));
+
+        facing(90.0, laser_hit(constants::tag::WALL, 2.0));
+        assert_eq!(has_line_of_sight(0.0, 5.0), Ok(false));
+    }
+
+    #[test]
+    fn has_line_of_sight_is_blocked_by_a_nearer_bot() {
+        // Another bot's component well in front of the target.
+        facing(90.0, laser_hit(constants::tag::COMPONENT, 1.0));
+        assert_eq!(has_line_of_sight(0.0, 10.0), Ok(false));
+
+        // The target's own nearest component, within the bot radius.
+        facing(90.0, laser_hit(constants::tag::COMPONENT, 8.0));
+        assert_eq!(has_line_of_sight(0.0, 10.0), Ok(true));
+    }
+
+    #[test]
+    fn confirmed_target_aims_relative_to_the_heading() {
+        facing(90.0, laser_hit(constants::tag::COMPONENT, 4.0));
+        let target = confirmed_target().unwrap().unwrap();
+        assert_eq!((target.x, target.y), (0.0, 5.0));
+        assert_eq!(target.tag, constants::tag::BOT);
+
+        facing(-90.0, laser_hit(constants::tag::COMPONENT, 4.0));
+        assert!(confirmed_target().unwrap().is_none());
+    }
+
+    #[test]
+    fn mine_self_risk_when_still_close_at_arming() {
+        assert!(mine_self_risk([0.0, 0.0], 3.0, 1.0, 1.5));
+        assert!(mine_self_risk([1.0, 0.0], 3.0, 1.0, 1.5));
+        assert!(!mine_self_risk([4.0, 0.0], 3.0, 1.0, 1.5));
+        assert!(!mine_self_risk([0.0, -2.0], 3.0, 1.0, 1.5));
+    }
+
+    #[test]
+    fn mine_self_risk_ignores_mines_arming_after_the_horizon() {
+        assert!(!mine_self_risk([0.0, 0.0], 0.5, 1.0, 1.5));
+    }
+
+    /// Answers as a Radar with a cooldown of `cooldown` seconds after each use,
+    /// on a clock advanced by `rbot::sleep`.
+    fn radar_cooling(cooldown: f32) {
+        let (mut used, mut remaining_until) = (false, 0.0);
+        mock::on_message(move |name, _| match name {
+            "MsgTime" => MessageType::RTime(msg::RMsgTime {
+                timestamp: mock::clock(),
+            }),
+            "MsgRadar" => {
+                used = true;
+                MessageType::RRadar(msg::RMsgRadar { x: 1.0, y: 2.0 })
+            }
+            "MsgAwaitAction" => {
+                if std::mem::take(&mut used) {
+                    remaining_until = mock::clock() + cooldown;
+                }
+                MessageType::Empty(msg::MsgEmpty { value: 0 })
+            }
+            "MsgModuleStatusQuery" => MessageType::RModuleStatus(msg::RMsgModuleStatus {
+                cooldown: (remaining_until - mock::clock()).max(0.0),
+            }),
+            _ => MessageType::Empty(msg::MsgEmpty { value: 0 }),
+        });
+    }
+
+    fn radar_count() -> usize {
+        mock::sent_names()
+            .iter()
+            .filter(|name| *name == "MsgRadar")
+            .count()
+    }
+
+    #[test]
+    fn sensor_cache_returns_the_cached_value_within_the_cooldown() {
+        radar_cooling(2.0);
+        let mut sensors = SensorCache::new();
+        assert_eq!(sensors.radar_cached().unwrap().x, 1.0);
+        crate::sleep(1.0);
+        assert_eq!(sensors.radar_cached().unwrap().y, 2.0);
+        assert_eq!(radar_count(), 1);
+    }
+
+    #[test]
+    fn sensor_cache_fetches_again_after_the_cooldown() {
+        radar_cooling(2.0);
+        let mut sensors = SensorCache::new();
+        sensors.radar_cached().unwrap();
+        crate::sleep(2.5);
+        sensors.radar_cached().unwrap();
+        assert_eq!(radar_count(), 2);
+
+        sensors.invalidate();
+        sensors.radar_cached().unwrap();
+        assert_eq!(radar_count(), 3);
+    }
+
+    fn object(x: f32, y: f32, tag: &str) -> msg::RMsgScanObject {
+        msg::RMsgScanObject {
+            x,
+            y,
+            tag: tag.to_string(),
+            kind: String::new(),
+            buffs: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn count_bots_groups_nearby_components() {
+        let component = constants::tag::COMPONENT;
+        let objects = [
+            object(10.0, 0.0, component),
+            object(11.0, 0.0, component),
+            object(12.0, 1.0, component),
+            object(-10.0, 5.0, component),
+            object(-10.0, 8.0, component),
+        ];
+        assert_eq!(count_bots(&objects, 3.0), 2);
+        assert_eq!(count_bots(&objects, 1.0), 4);
+        assert_eq!(count_bots(&[], 3.0), 0);
+    }
+
+    #[test]
+    fn count_bots_chains_components_and_ignores_other_objects() {
+        let component = constants::tag::COMPONENT;
+        let objects = [
+            object(0.0, 0.0, component),
+            object(2.0, 0.0, component),
+            object(4.0, 0.0, component),
+            object(1.0, 1.0, constants::tag::WALL),
+            object(30.0, 0.0, constants::tag::WALL),
+        ];
+        assert_eq!(count_bots(&objects, 2.0), 1);
+    }
+
+    #[test]
+    fn quadrant_counts_axes_as_north_and_east() {
+        assert_eq!(Quadrant::of(3.0, 4.0), Quadrant::NorthEast);
+        assert_eq!(Quadrant::of(-3.0, 4.0), Quadrant::NorthWest);
+        assert_eq!(Quadrant::of(3.0, -4.0), Quadrant::SouthEast);
+        assert_eq!(Quadrant::of(-3.0, -4.0), Quadrant::SouthWest);
+        assert_eq!(Quadrant::of(0.0, 0.0), Quadrant::NorthEast);
+        assert_eq!(Quadrant::of(-1.0, 0.0), Quadrant::NorthWest);
+        assert_eq!(Quadrant::of(0.0, -1.0), Quadrant::SouthEast);
+    }
+
+    #[test]
+    fn gps_detailed_derives_distance_and_quadrant() {
+        mock::on_message(|name, _| match name {
+            "MsgGPS" => MessageType::RGPS(msg::RMsgGPS { x: -3.0, y: 4.0 }),
+            _ => MessageType::Empty(msg::MsgEmpty { value: 0 }),
+        });
+        let gps = gps_detailed().unwrap();
+        assert_eq!(
+            gps,
+            GpsInfo {
+                x: -3.0,
+                y: 4.0,
+                distance_from_center: 5.0,
+                quadrant: Quadrant::NorthWest,
+            }
+        );
+    }
+
+    #[test]
+    fn auto_repair_tick_repairs_once_per_cooldown() {
+        let (mut queued, mut cooldown) = (false, 0.0);
+        mock::on_message(move |name, json| match name {
+            "MsgModuleStatusQuery" => {
+                MessageType::RModuleStatus(msg::RMsgModuleStatus { cooldown })
+            }
+            "MsgComponentStatusQuery" => MessageType::RComponentStatus(msg::RMsgComponentStatus {
+                health: if json["component_id"] == 1 {
+                    20.0
+                } else {
+                    80.0
+                },
+                cooldown: 0.0,
+                is_activated: false,
+            }),
+            "MsgRepair" => {
+                queued = true;
+                MessageType::RRepair(msg::RMsgRepair { health: 20.0 })
+            }
+            "MsgAwaitAction" => {
+                if std::mem::take(&mut queued) {
+                    cooldown = 5.0;
+                }
+                MessageType::Empty(msg::MsgEmpty { value: 0 })
+            }
+            _ => MessageType::Empty(msg::MsgEmpty { value: 0 }),
+        });
+        assert_eq!(auto_repair_tick(&[0, 1, 2], 50.0), Ok(Some(1)));
+        assert_eq!(auto_repair_tick(&[0, 1, 2], 50.0), Ok(None));
+        let repairs = mock::sent_names()
+            .iter()
+            .filter(|name| *name == "MsgRepair")
+            .count();
+        assert_eq!(repairs, 1);
+    }
+
+    #[test]
+    fn module_ids_round_trip() {
+        for module in Module::iter() {
+            let id = module as i32;
+            assert_eq!(Module::from_id(id).map(|m| m as i32), Some(id));
+        }
+        assert!(Module::from_id(-1).is_none());
+        assert!(Module::from_id(Module::iter().count() as i32).is_none());
+    }
+
+    #[test]
+    fn thrust_sustained_waits_for_the_cooldown_before_each_pulse() {
+        let (mut thrusted, mut ready_at) = (false, 0.0);
+        mock::on_message(move |name, _| match name {
+            "MsgThrust" => {
+                thrusted = true;
+                MessageType::Empty(msg::MsgEmpty { value: 0 })
+            }
+            "MsgAwaitAction" => {
+                if std::mem::take(&mut thrusted) {
+                    ready_at = mock::clock() + 0.5;
+                }
+                MessageType::Empty(msg::MsgEmpty { value: 0 })
+            }
+            "MsgModuleStatusQuery" => MessageType::RModuleStatus(msg::RMsgModuleStatus {
+                cooldown: (ready_at - mock::clock()).max(0.0),
+            }),
+            _ => MessageType::Empty(msg::MsgEmpty { value: 0 }),
+        });
+        thrust_sustained(45.0, 3).unwrap();
+        let pulses: Vec<_> = mock::sent()
+            .into_iter()
+            .filter(|(name, _)| name == "MsgThrust")
+            .map(|(_, json)| json["angle"].as_f64().unwrap())
+            .collect();
+        assert_eq!(pulses, [45.0, 45.0, 45.0]);
+        // The second and third pulse each waited out a cooldown.
+        assert!(mock::clock() >= 1.0 && mock::clock() < 1.1);
+    }
+
+    /// Answers radar pulses with the given positions in turn, with a radar
+    /// cooldown of 1 second on a clock advanced by `rbot::sleep`.
+    fn radar_reporting(positions: Vec<(f32, f32)>) {
+        let mut positions = positions.into_iter();
+        let (mut used, mut ready_at) = (false, 0.0);
+        mock::on_message(move |name, _| match name {
+            "MsgTime" => MessageType::RTime(msg::RMsgTime {
+                timestamp: mock::clock(),
+            }),
+            "MsgRadar" => {
+                used = true;
+                let (x, y) = positions.next().unwrap_or((0.0, 0.0));
+                MessageType::RRadar(msg::RMsgRadar { x, y })
+            }
+            "MsgAwaitAction" => {
+                if std::mem::take(&mut used) {
+                    ready_at = mock::clock() + 1.0;
+                }
+                MessageType::Empty(msg::MsgEmpty { value: 0 })
+            }
+            "MsgModuleStatusQuery" => MessageType::RModuleStatus(msg::RMsgModuleStatus {
+                cooldown: (ready_at - mock::clock()).max(0.0),
+            }),
+            _ => MessageType::Empty(msg::MsgEmpty { value: 0 }),
+        });
+    }
+
+    #[test]
+    fn radar_is_nonzero_treats_the_origin_as_no_contact() {
+        assert!(!radar_is_nonzero(&msg::RMsgRadar { x: 0.0, y: 0.0 }));
+        assert!(radar_is_nonzero(&msg::RMsgRadar { x: 0.0, y: -3.0 }));
+        assert!(radar_is_nonzero(&msg::RMsgRadar { x: 2.0, y: 0.0 }));
+    }
+
+    #[test]
+    fn radar_until_contact_times_out_with_none() {
+        radar_reporting(Vec::new());
+        assert!(radar_until_contact(2.5).unwrap().is_none());
+        assert!(mock::clock() >= 2.5);
+        assert_eq!(radar_count(), 4);
+    }
+
+    #[test]
+    fn radar_until_contact_returns_the_first_contact() {
+        radar_reporting(vec![(0.0, 0.0), (3.0, 4.0)]);
+        let radar_msg = radar_until_contact(10.0).unwrap().unwrap();
+        assert_eq!((radar_msg.x, radar_msg.y), (3.0, 4.0));
+        assert_eq!(radar_count(), 2);
+    }
+
+    /// Answers a single scan with `objects`, as a robot facing 0 with every
+    /// module ready. The laser hits a wall at distance 2 towards
+    /// `blocked_angle` and a component at distance 10 in every other direction.
+    fn scanning(objects: Vec<msg::RMsgScanObject>, blocked_angle: f64) {
+        let mut objects = Some(objects);
+        mock::on_message(move |name, json| match name {
+            "MsgScan" => MessageType::RScan(msg::RMsgScan {
+                objects: objects.take().unwrap(),
+            }),
+            "MsgState" => MessageType::RState(msg::RMsgState {
+                angle: 0.0,
+                health: 100.0,
+                buffs: Vec::new(),
+            }),
+            "MsgModuleStatusQuery" => {
+                MessageType::RModuleStatus(msg::RMsgModuleStatus { cooldown: 0.0 })
+            }
+            "MsgLaser" if json["angle"] == blocked_angle => laser_hit(constants::tag::WALL, 2.0),
+            "MsgLaser" => laser_hit(constants::tag::COMPONENT, 10.0),
+            _ => MessageType::Empty(msg::MsgEmpty { value: 0 }),
+        });
+    }
+
+    #[test]
+    fn scan_for_bot_picks_the_nearest_cluster() {
+        let component = constants::tag::COMPONENT;
+        let mut motherboard = object(20.0, 1.0, component);
+        motherboard.kind = constants::kind::MOTHERBOARD.to_string();
+        scanning(
+            vec![
+                object(20.0, 0.0, component),
+                motherboard,
+                object(0.0, -6.0, component),
+                object(1.0, -6.0, component),
+            ],
+            -1.0,
+        );
+        let bot = scan_for_bot().unwrap().unwrap();
+        assert_eq!((bot.x, bot.y), (0.5, -6.0));
+        assert_eq!(bot.tag, constants::tag::BOT);
+    }
+
+    #[test]
+    fn best_target_skips_the_blocked_enemy() {
+        let component = constants::tag::COMPONENT;
+        scanning(
+            vec![object(5.0, 0.0, component), object(0.0, 10.0, component)],
+            0.0,
+        );
+        let target = best_target().unwrap().unwrap();
+        assert_eq!((target.x, target.y), (0.0, 10.0));
+        let lasers: Vec<_> = mock::sent()
+            .into_iter()
+            .filter(|(name, _)| name == "MsgLaser")
+            .map(|(_, json)| json["angle"].as_f64().unwrap())
+            .collect();
+        assert_eq!(lasers, [0.0, 90.0]);
+    }
+
+    #[test]
+    fn thrust_towards_converts_the_direction_to_an_angle() {
+        mock::on_message(|_, _| MessageType::Empty(msg::MsgEmpty { value: 0 }));
+        thrust_towards(0.0, 1.0).unwrap();
+        thrust_towards(-2.0, 0.0).unwrap();
+        thrust_towards(0.0, -3.0).unwrap();
+        let angles: Vec<_> = mock::sent()
+            .into_iter()
+            .map(|(_, json)| json["angle"].as_f64().unwrap() as f32)
+            .collect();
+        for (actual, expected) in angles.into_iter().zip([90.0, 180.0, 270.0]) {
+            assert!((actual - expected).abs() < 1e-3, "{actual} != {expected}");
+        }
+    }
+
+    /// Answers laser scans with `hits` in turn.
+    fn lasers(hits: Vec<MessageType>) {
+        let mut hits = hits.into_iter();
+        mock::on_message(move |name, _| match name {
+            "MsgLaser" => hits.next().unwrap(),
+            _ => MessageType::Empty(msg::MsgEmpty { value: 0 }),
+        });
+    }
+
+    #[test]
+    fn laser_typed_maps_each_tag_to_its_variant() {
+        let component = MessageType::RLaser(msg::RMsgLaser {
+            tag: constants::tag::COMPONENT.to_string(),
+            kind: constants::kind::MOTHERBOARD.to_string(),
+            distance: 7.0,
+            angle: 0.0,
+            buffs: vec!["Shield".to_string()],
+        });
+        lasers(vec![
+            laser_hit("", 0.0),
+            laser_hit(constants::tag::WALL, 1.0),
+            laser_hit(constants::tag::SENTRY, 2.0),
+            component,
+            laser_hit("Rock", 3.0),
+        ]);
+        assert_eq!(laser_typed(0.0), Ok(LaserHit::None));
+        assert_eq!(laser_typed(0.0), Ok(LaserHit::Wall { distance: 1.0 }));
+        assert_eq!(laser_typed(0.0), Ok(LaserHit::Sentry { distance: 2.0 }));
+        assert_eq!(
+            laser_typed(0.0),
+            Ok(LaserHit::Component {
+                kind: constants::kind::MOTHERBOARD.to_string(),
+                distance: 7.0,
+                buffs: vec!["Shield".to_string()],
+            })
+        );
+        assert_eq!(
+            laser_typed(0.0),
+            Ok(LaserHit::Other {
+                tag: "Rock".to_string(),
+                distance: 3.0,
+            })
+        );
+    }
+
+    #[test]
+    fn range_to_returns_the_distance_of_any_hit() {
+        lasers(vec![
+            laser_hit(constants::tag::WALL, 4.5),
+            laser_hit(constants::tag::COMPONENT, 9.0),
+            laser_hit("", 0.0),
+        ]);
+        assert_eq!(range_to(90.0), Ok(Some(4.5)));
+        assert_eq!(range_to(90.0), Ok(Some(9.0)));
+        assert_eq!(range_to(90.0), Ok(None));
+        assert_eq!(mock::sent()[0].1["angle"], 90.0);
+    }
+
+    #[test]
+    fn scan_bot_detailed_reports_the_motherboard_and_spread() {
+        let component = constants::tag::COMPONENT;
+        let mut motherboard = object(0.0, -6.0, component);
+        motherboard.kind = constants::kind::MOTHERBOARD.to_string();
+        scanning(
+            vec![
+                object(20.0, 0.0, component),
+                object(2.0, -6.0, component),
+                motherboard,
+                object(0.0, -4.0, component),
+            ],
+            -1.0,
+        );
+        let bot = scan_bot_detailed().unwrap().unwrap();
+        assert_eq!(bot.motherboard, Some([0.0, -6.0]));
+        assert_eq!(bot.component_count, 3);
+        assert!((bot.center[0] - 2.0 / 3.0).abs() < 1e-5);
+        assert!((bot.center[1] + 16.0 / 3.0).abs() < 1e-5);
+        assert!((bot.spread - 2.0_f32.hypot(2.0)).abs() < 1e-5);
+
+        scanning(vec![object(1.0, 1.0, constants::tag::WALL)], -1.0);
+        assert_eq!(scan_bot_detailed(), Ok(None));
+    }
+
+    #[test]
+    fn await_module_and_await_cooldown_wait_out_the_cooldown() {
+        radar_cooling(0.5);
+        radar().unwrap();
+        await_module(Module::Radar).unwrap();
+        assert!((0.5..0.52).contains(&mock::clock()), "{}", mock::clock());
+
+        radar().unwrap();
+        await_cooldown(Module::Radar).unwrap();
+        assert!((1.0..1.04).contains(&mock::clock()), "{}", mock::clock());
+
+        // Nothing to wait for once the cooldown is over.
+        let slept = mock::slept().len();
+        await_module(Module::Radar).unwrap();
+        assert_eq!(mock::slept().len(), slept);
+    }
+
+    #[test]
+    fn module_methods_query_their_own_module() {
+        radar_cooling(0.5);
+        assert_eq!(Module::Radar.status().unwrap().cooldown, 0.0);
+        assert_eq!(Module::Radar.is_ready(), Ok(true));
+
+        radar().unwrap();
+        await_action().unwrap();
+        assert_eq!(Module::Radar.status().unwrap().cooldown, 0.5);
+        assert_eq!(Module::Radar.is_ready(), Ok(false));
+
+        Module::Radar.await_ready().unwrap();
+        assert!((0.5..0.52).contains(&mock::clock()), "{}", mock::clock());
+        assert_eq!(Module::Radar.is_ready(), Ok(true));
+
+        let queried: Vec<_> = mock::sent()
+            .into_iter()
+            .filter(|(name, _)| name == "MsgModuleStatusQuery")
+            .map(|(_, json)| json["module_id"].as_i64().unwrap())
+            .collect();
+        assert!(queried.iter().all(|id| *id == Module::Radar as i64));
+    }
+
+    #[test]
+    fn absolute_and_relative_positions_round_trip() {
+        mock::on_message(|name, _| match name {
+            "MsgGPS" => MessageType::RGPS(msg::RMsgGPS { x: 12.0, y: -4.5 }),
+            _ => MessageType::Empty(msg::MsgEmpty { value: 0 }),
+        });
+        assert_eq!(absolute_to_relative([0.0, 0.0]), Ok([-12.0, 4.5]));
+        assert_eq!(relative_to_absolute([3.0, 1.5]), Ok([15.0, -3.0]));
+        for position in [[0.0, 0.0], [20.0, -7.25], [-3.5, 9.0]] {
+            let relative = absolute_to_relative(position).unwrap();
+            assert_eq!(relative_to_absolute(relative), Ok(position));
+        }
+    }
+}
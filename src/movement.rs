@@ -0,0 +1,720 @@
+use crate::constants::map::ArenaBounds;
+use crate::conversions;
+use crate::core;
+use crate::errors::MessageError;
+use crate::modules;
+use crate::modules::Module;
+use crate::rotations;
+
+/// Reads the robot's absolute position using GPS, waiting for its cooldown first.
+fn position() -> Result<[f32; 2], MessageError> {
+    modules::await_module(Module::GPS)?;
+    let gps = modules::gps()?;
+    Ok([gps.x, gps.y])
+}
+
+/// State of an Archimedean spiral search around a fixed center.
+///
+/// The spiral starts at `center` and grows by `spacing` for every full turn.
+/// Each call to `spiral_search_step` advances the spiral by `angle_step`
+/// degrees.
+pub struct SpiralState {
+    /// The absolute position (from the center of the map) the spiral is centered on.
+    pub center: [f32; 2],
+    /// The distance between two successive turns of the spiral.
+    pub spacing: f32,
+    /// The angle in degrees the spiral advances per step.
+    pub angle_step: f32,
+    angle: f32,
+}
+
+impl SpiralState {
+    /// Creates a new spiral centered on the absolute position (`x`, `y`).
+    pub fn new(x: f32, y: f32, spacing: f32, angle_step: f32) -> Self {
+        Self {
+            center: [x, y],
+            spacing,
+            angle_step,
+            angle: 0.0,
+        }
+    }
+
+    /// The current distance of the spiral from its center.
+    pub fn radius(&self) -> f32 {
+        self.spacing * self.angle / 360.0
+    }
+
+    /// Advances the spiral by one step and returns the next absolute point on it.
+    pub fn advance(&mut self) -> [f32; 2] {
+        self.angle += self.angle_step;
+        let radius = self.radius();
+        let [x, y] = conversions::angle_to_xy(self.angle);
+        [self.center[0] + x * radius, self.center[1] + y * radius]
+    }
+}
+
+/// Moves the robot one step along an expanding spiral search pattern.
+///
+/// This function is stateful: it advances `state` to the next point on the
+/// spiral and sets the robot's velocity towards that point, using GPS to read
+/// the robot's current position. Call it once per tick, for example while the
+/// radar does not detect an enemy.
+///
+/// # Arguments
+///
+/// * `state` - The spiral to follow, advanced by one step on each call.
+/// * `speed` - The speed of traversal (between 0 and 1).
+///
+/// # Returns
+///
+/// Returns `Ok(())` if the velocity command was sent successfully, or an error
+/// of type `MessageError` if there was a problem during communication with the
+/// game server.
+///
+/// # Examples
+///
+/// ```
+/// let gps = rbot::modules::gps()?;
+/// let mut spiral = rbot::movement::SpiralState::new(gps.x, gps.y, 5.0, 15.0);
+/// loop {
+///     rbot::movement::spiral_search_step(&mut spiral, 1.0)?;
+///     rbot::sleep(0.1);
+/// }
+/// ```
+pub fn spiral_search_step(state: &mut SpiralState, speed: f32) -> Result<(), MessageError> {
+    let [target_x, target_y] = state.advance();
+    let [x, y] = position()?;
+    core::velocity(target_x - x, target_y - y, speed)
+}
+
+/// Maximum number of thruster pulses `dash_to` fires before giving up.
+pub const DASH_MAX_PULSES: u32 = 32;
+
+/// Dashes the robot to a position relative to it using repeated thruster pulses.
+///
+/// This function is a supported replacement for the deprecated `teleport`. It
+/// records the absolute target position using GPS, then repeatedly waits for
+/// the Thruster cooldown and thrusts towards the target until the robot is
+/// within `tolerance` of it.
+///
+/// This function blocks until the target is reached, including every wait for
+/// the GPS and Thruster cooldowns in between pulses. It gives up after
+/// `DASH_MAX_PULSES` pulses, e.g. when a wall blocks the way.
+///
+/// # Arguments
+///
+/// * `x` - The x-coordinate of the target relative to the robot's position.
+/// * `y` - The y-coordinate of the target relative to the robot's position.
+/// * `tolerance` - The distance from the target at which the dash is complete.
+///   Must be greater than 0.
+///
+/// # Returns
+///
+/// Returns `Ok(())` once the robot is within `tolerance` of the target,
+/// `MessageError::InvalidArgument` if `tolerance` is not greater than 0,
+/// `MessageError::Timeout` if the target is not reached within
+/// `DASH_MAX_PULSES` pulses, or another error of type `MessageError` if there
+/// was a problem during communication with the game server.
+///
+/// # Examples
+///
+/// ```
+/// // Dash 10 units to the right.
+/// rbot::movement::dash_to(10.0, 0.0, 1.0)?;
+/// ```
+pub fn dash_to(x: f32, y: f32, tolerance: f32) -> Result<(), MessageError> {
+    if tolerance.is_nan() || tolerance <= 0.0 {
+        return Err(MessageError::InvalidArgument(
+            "dash tolerance must be greater than 0",
+        ));
+    }
+    let [start_x, start_y] = position()?;
+    let target = [start_x + x, start_y + y];
+
+    for _ in 0..DASH_MAX_PULSES {
+        let [x, y] = position()?;
+        let (dx, dy) = (target[0] - x, target[1] - y);
+        if dx.hypot(dy) <= tolerance {
+            return Ok(());
+        }
+        modules::await_module(Module::Thruster)?;
+        modules::thrust(conversions::xy_to_angle_360(dx, dy))?;
+    }
+    let [x, y] = position()?;
+    if (target[0] - x).hypot(target[1] - y) <= tolerance {
+        return Ok(());
+    }
+    Err(MessageError::Timeout)
+}
+
+/// Moves the robot to an absolute position on the map and stops there.
+///
+/// The robot's position is read with GPS, and the velocity is steered towards
+/// the target until the robot is within `tolerance` of it. This function
+/// blocks until the target is reached, including every wait for the GPS
+/// cooldown in between. It gives up after `DRIVE_MAX_STEPS` position checks,
+/// e.g. when a wall blocks the way.
+///
+/// # Arguments
+///
+/// * `x` - The x-coordinate of the target from the center of the map.
+/// * `y` - The y-coordinate of the target from the center of the map.
+/// * `speed` - The speed of traversal (between 0 and 1).
+/// * `tolerance` - The distance from the target at which the move is complete.
+///
+/// # Returns
+///
+/// Returns `Ok(())` once the robot is within `tolerance` of the target and has
+/// been stopped, `MessageError::Timeout` if the target is not reached within
+/// `DRIVE_MAX_STEPS` steps, or another error of type `MessageError` if there
+/// was a problem during communication with the game server.
+///
+/// # Examples
+///
+/// ```
+/// // Move to the center of the map.
+/// rbot::movement::move_to(0.0, 0.0, 1.0, 2.0)?;
+/// ```
+pub fn move_to(x: f32, y: f32, speed: f32, tolerance: f32) -> Result<(), MessageError> {
+    drive_to(x, y, speed, tolerance)?;
+    core::stop()
+}
+
+/// Maximum number of position checks `move_to` and `follow_path` make per
+/// target before giving up.
+pub const DRIVE_MAX_STEPS: u32 = 1_000;
+
+/// Drives the robot to an absolute position on the map without stopping there.
+fn drive_to(x: f32, y: f32, speed: f32, tolerance: f32) -> Result<(), MessageError> {
+    for _ in 0..DRIVE_MAX_STEPS {
+        let [pos_x, pos_y] = position()?;
+        let (dx, dy) = (x - pos_x, y - pos_y);
+        if dx.hypot(dy) <= tolerance {
+            return Ok(());
+        }
+        core::velocity(dx, dy, speed)?;
+    }
+    core::stop()?;
+    Err(MessageError::Timeout)
+}
+
+/// Drives the robot along a path of absolute positions and stops at its end.
+///
+/// The robot drives towards each waypoint in turn like `move_to`, but only
+/// stops after the last one, and moves on to the next waypoint as soon as it is
+/// within `waypoint_tolerance` of the current one. A path found with
+/// `rbot::nav::astar` can be converted to waypoints with
+/// `rbot::nav::cell_center` and `rbot::modules::relative_to_absolute`. This
+/// function blocks until the end of the path is reached. An empty path does
+/// nothing.
+///
+/// # Arguments
+///
+/// * `path` - The absolute positions `[x, y]` from the center of the map to visit.
+/// * `speed` - The speed of traversal (between 0 and 1).
+/// * `waypoint_tolerance` - The distance from a waypoint at which it counts as reached.
+///
+/// # Returns
+///
+/// Returns `Ok(())` once the robot has reached the last waypoint and has been
+/// stopped, `MessageError::Timeout` if a waypoint is not reached within
+/// `DRIVE_MAX_STEPS` steps, or another error of type `MessageError` if there
+/// was a problem during communication with the game server.
+///
+/// # Examples
+///
+/// ```
+/// // Drive around a square.
+/// let path = [[10.0, 10.0], [-10.0, 10.0], [-10.0, -10.0], [10.0, -10.0]];
+/// rbot::movement::follow_path(&path, 1.0, 2.0)?;
+/// ```
+pub fn follow_path(
+    path: &[[f32; 2]],
+    speed: f32,
+    waypoint_tolerance: f32,
+) -> Result<(), MessageError> {
+    if path.is_empty() {
+        return Ok(());
+    }
+    for [x, y] in path {
+        drive_to(*x, *y, speed, waypoint_tolerance)?;
+    }
+    core::stop()
+}
+
+/// Moves the robot to the arena corner closest to it and stops there.
+///
+/// Sitting in a corner leaves fewer directions to be attacked from, which is
+/// useful to survive with low health. The corner itself is on the walls and
+/// cannot be reached, so the robot drives to the point `margin` units inside
+/// both walls. This function blocks until that point is reached, see
+/// `move_to`.
+///
+/// # Arguments
+///
+/// * `arena` - The bounds of the arena the robot is in.
+/// * `margin` - The distance to keep from both walls. Must be greater than 0.
+/// * `speed` - The speed of traversal (between 0 and 1).
+/// * `tolerance` - The distance from the target point at which the retreat is complete.
+///
+/// # Returns
+///
+/// Returns `Ok(())` once the robot is within `tolerance` of the target point,
+/// `MessageError::InvalidArgument` if `margin` is not greater than 0,
+/// `MessageError::Timeout` if the point is not reached, see `move_to`, or
+/// another error of type `MessageError` if there was a problem during
+/// communication with the game server.
+///
+/// # Examples
+///
+/// ```
+/// use rbot::constants::map::ArenaBounds;
+///
+/// let arena = ArenaBounds::new(40.0, 25.0);
+/// rbot::on_low_health(30.0, || {
+///     rbot::movement::retreat_to_corner(&arena, 4.0, 1.0, 2.0)
+/// })?;
+/// ```
+pub fn retreat_to_corner(
+    arena: &ArenaBounds,
+    margin: f32,
+    speed: f32,
+    tolerance: f32,
+) -> Result<(), MessageError> {
+    if margin.is_nan() || margin <= 0.0 {
+        return Err(MessageError::InvalidArgument(
+            "corner margin must be greater than 0",
+        ));
+    }
+    let [x, y] = nearest_corner(arena, margin, position()?);
+    move_to(x, y, speed, tolerance)
+}
+
+/// The point `margin` units inside the arena corner closest to the absolute position.
+fn nearest_corner(arena: &ArenaBounds, margin: f32, [x, y]: [f32; 2]) -> [f32; 2] {
+    [
+        (arena.half_width - margin).max(0.0).copysign(x),
+        (arena.half_height - margin).max(0.0).copysign(y),
+    ]
+}
+
+/// Strafes sideways relative to a threat for a short time, then stops.
+///
+/// Moving perpendicular to the line of fire is the quickest way out of it.
+/// Seen from the robot facing the threat, `left` moves to the left (the threat
+/// angle plus 90°) and otherwise to the right (minus 90°). This function
+/// blocks for `duration_secs`, see `rbot::velocity_for`.
+///
+/// # Arguments
+///
+/// * `threat_angle_deg` - The global angle in degrees towards the threat.
+/// * `left` - Whether to dodge to the left instead of the right.
+/// * `speed` - The speed of traversal (between 0 and 1).
+/// * `duration_secs` - How long to move in seconds.
+///
+/// # Returns
+///
+/// Returns `Ok(())` once the robot has been stopped, or an error of type
+/// `MessageError` if there was a problem during communication with the game
+/// server.
+///
+/// # Examples
+///
+/// ```
+/// let radar_msg = rbot::modules::radar()?;
+/// let threat = rbot::conversions::xy_to_angle(radar_msg.x, radar_msg.y);
+/// rbot::movement::dodge(threat, rbot::random() < 0.5, 1.0, 0.3)?;
+/// ```
+pub fn dodge(
+    threat_angle_deg: f32,
+    left: bool,
+    speed: f32,
+    duration_secs: f32,
+) -> Result<(), MessageError> {
+    let [x, y] = conversions::angle_to_xy(dodge_angle(threat_angle_deg, left));
+    core::velocity_for(x, y, speed, duration_secs)
+}
+
+/// The direction perpendicular to the threat on the given side.
+fn dodge_angle(threat_angle_deg: f32, left: bool) -> f32 {
+    let offset = if left { 90.0 } else { -90.0 };
+    rotations::normalize_angle(threat_angle_deg + offset)
+}
+
+/// The side of the robot to keep a wall on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    Left,
+    Right,
+}
+
+/// Degrees of steering per unit of distance error when following a wall.
+const WALL_FOLLOW_GAIN: f32 = 10.0;
+
+/// Maximum steering angle in degrees away from the wall direction when following a wall.
+const WALL_FOLLOW_MAX_CORRECTION: f32 = 45.0;
+
+/// Angle in degrees between each of the two wall probes and the side of the robot.
+const WALL_PROBE_SPREAD: f32 = 15.0;
+
+/// Moves the robot one step along a wall, keeping it at a fixed distance.
+///
+/// This function fires two lasers towards the given `side` of the robot, a
+/// little ahead of and behind the perpendicular, and fits the wall through
+/// both hits. The robot then travels along the wall, steering slightly
+/// towards it when too far from it, or away from it when too close. The
+/// travel direction only depends on the measured wall, not on the robot's
+/// body rotation. If a laser hits nothing, the robot steers towards `side` to
+/// find a wall. Each laser waits for the Laser cooldown first. Call it once
+/// per tick.
+///
+/// # Arguments
+///
+/// * `side` - The side of the robot the wall should be on.
+/// * `desired_distance` - The distance to keep from the wall.
+/// * `speed` - The speed of traversal (between 0 and 1).
+///
+/// # Returns
+///
+/// Returns `Ok(())` if the velocity command was sent successfully, or an error
+/// of type `MessageError` if there was a problem during communication with the
+/// game server.
+///
+/// # Examples
+///
+/// ```
+/// use rbot::movement::Side;
+///
+/// loop {
+///     rbot::movement::wall_follow_step(Side::Right, 3.0, 0.8)?;
+///     rbot::sleep(0.1);
+/// }
+/// ```
+pub fn wall_follow_step(side: Side, desired_distance: f32, speed: f32) -> Result<(), MessageError> {
+    let heading = core::state()?.angle;
+    // The laser angle pointing from the robot's orientation towards the wall side.
+    let towards_wall = match side {
+        Side::Left => 90.0,
+        Side::Right => -90.0,
+    };
+
+    let mut hits = [None; 2];
+    for (hit, offset) in hits.iter_mut().zip([-WALL_PROBE_SPREAD, WALL_PROBE_SPREAD]) {
+        let angle = rotations::normalize_angle(towards_wall + offset);
+        modules::await_module(Module::Laser)?;
+        let laser_msg = modules::laser(angle)?;
+        if !laser_msg.tag.is_empty() {
+            *hit = Some(conversions::from_polar(laser_msg.distance, heading + angle));
+        }
+    }
+
+    let direction = match hits {
+        [Some(a), Some(b)] => wall_normal(a, b).map(|(normal, distance)| {
+            let steer = wall_follow_correction(distance, desired_distance);
+            normal - towards_wall + steer * towards_wall.signum()
+        }),
+        _ => None,
+    };
+    let direction =
+        direction.unwrap_or(heading + WALL_FOLLOW_MAX_CORRECTION * towards_wall.signum());
+    let [x, y] = conversions::angle_to_xy(direction);
+    core::velocity(x, y, speed)
+}
+
+/// The world angle in degrees of the wall normal pointing from the robot
+/// towards the wall through the points `a` and `b` relative to the robot, and
+/// the distance to the wall, or `None` if the points coincide.
+fn wall_normal(a: [f32; 2], b: [f32; 2]) -> Option<(f32, f32)> {
+    let along = [b[0] - a[0], b[1] - a[1]];
+    let length_sq = along[0] * along[0] + along[1] * along[1];
+    if length_sq <= f32::EPSILON {
+        return None;
+    }
+    // The point on the wall closest to the robot.
+    let t = (a[0] * along[0] + a[1] * along[1]) / length_sq;
+    let foot = [a[0] - t * along[0], a[1] - t * along[1]];
+    let (distance, normal) = conversions::to_polar(foot[0], foot[1]);
+    Some((normal, distance))
+}
+
+/// Steering correction in degrees towards the wall (positive) or away from it
+/// (negative) for the measured and desired wall distance.
+fn wall_follow_correction(distance: f32, desired_distance: f32) -> f32 {
+    ((distance - desired_distance) * WALL_FOLLOW_GAIN)
+        .clamp(-WALL_FOLLOW_MAX_CORRECTION, WALL_FOLLOW_MAX_CORRECTION)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hostfn::mock;
+    use rbot_messages::messages as msg;
+    use rbot_messages::MessageType;
+
+    /// Answers as a robot stuck at the absolute position (`x`, `y`) with every
+    /// module ready.
+    fn stuck_at(x: f32, y: f32) {
+        mock::on_message(move |name, _| match name {
+            "MsgGPS" => MessageType::RGPS(msg::RMsgGPS { x, y }),
+            "MsgModuleStatusQuery" => {
+                MessageType::RModuleStatus(msg::RMsgModuleStatus { cooldown: 0.0 })
+            }
+            _ => MessageType::Empty(msg::MsgEmpty { value: 0 }),
+        });
+    }
+
+    fn thrust_angles() -> Vec<f32> {
+        mock::sent()
+            .into_iter()
+            .filter(|(name, _)| name == "MsgThrust")
+            .map(|(_, json)| json["angle"].as_f64().unwrap() as f32)
+            .collect()
+    }
+
+    #[test]
+    fn dash_to_rejects_non_positive_tolerance() {
+        stuck_at(0.0, 0.0);
+        for tolerance in [0.0, -1.0, f32::NAN] {
+            assert!(matches!(
+                dash_to(5.0, 0.0, tolerance),
+                Err(MessageError::InvalidArgument(_))
+            ));
+        }
+        assert!(mock::sent().is_empty());
+    }
+
+    #[test]
+    fn dash_to_gives_up_when_stuck() {
+        stuck_at(3.0, 4.0);
+        assert_eq!(dash_to(0.0, -5.0, 1.0), Err(MessageError::Timeout));
+        let angles = thrust_angles();
+        assert_eq!(angles.len(), DASH_MAX_PULSES as usize);
+        assert!(angles.iter().all(|&angle| angle == 270.0));
+    }
+
+    #[test]
+    fn dash_to_returns_within_tolerance() {
+        stuck_at(3.0, 4.0);
+        assert_eq!(dash_to(0.5, 0.0, 1.0), Ok(()));
+        assert!(thrust_angles().is_empty());
+    }
+
+    fn assert_angle(actual: f32, expected: f32) {
+        assert!(
+            rotations::angle_distance(actual, expected) < 1e-3,
+            "{actual} != {expected}"
+        );
+    }
+
+    #[test]
+    fn wall_normal_is_independent_of_the_probe_points() {
+        // A wall along y = -3, below the robot.
+        let (normal, distance) = wall_normal([-1.0, -3.0], [4.0, -3.0]).unwrap();
+        assert_angle(normal, -90.0);
+        assert!((distance - 3.0).abs() < 1e-5);
+
+        // A diagonal wall through (2, 0) and (0, 2).
+        let (normal, distance) = wall_normal([0.0, 2.0], [2.0, 0.0]).unwrap();
+        assert_angle(normal, 45.0);
+        assert!((distance - 2.0f32.sqrt()).abs() < 1e-5);
+
+        assert_eq!(wall_normal([1.0, 1.0], [1.0, 1.0]), None);
+    }
+
+    /// Answers as a robot at the origin facing up, with a wall along
+    /// x = `wall_x` and every module ready.
+    fn wall_at(wall_x: f32) {
+        let heading = 90.0;
+        mock::on_message(move |name, json| match name {
+            "MsgState" => MessageType::RState(msg::RMsgState {
+                angle: heading,
+                health: 100.0,
+                buffs: Vec::new(),
+            }),
+            "MsgModuleStatusQuery" => {
+                MessageType::RModuleStatus(msg::RMsgModuleStatus { cooldown: 0.0 })
+            }
+            "MsgLaser" => {
+                let angle = heading + json["angle"].as_f64().unwrap() as f32;
+                let [x, _] = conversions::angle_to_xy(angle);
+                MessageType::RLaser(msg::RMsgLaser {
+                    tag: crate::constants::tag::WALL.to_string(),
+                    kind: String::new(),
+                    distance: wall_x / x,
+                    angle: 0.0,
+                    buffs: Vec::new(),
+                })
+            }
+            _ => MessageType::Empty(msg::MsgEmpty { value: 0 }),
+        });
+    }
+
+    /// The direction of the velocity sent.
+    fn velocity_sent() -> [f32; 2] {
+        let (_, velocity) = mock::sent()
+            .into_iter()
+            .find(|(name, _)| name == "MsgVelocity")
+            .unwrap();
+        let x = velocity["x"].as_f64().unwrap() as f32;
+        let y = velocity["y"].as_f64().unwrap() as f32;
+        [x, y]
+    }
+
+    #[test]
+    fn wall_follow_step_travels_along_the_wall() {
+        wall_at(3.0);
+        wall_follow_step(Side::Right, 3.0, 1.0).unwrap();
+
+        let lasers: Vec<_> = mock::sent()
+            .into_iter()
+            .filter(|(name, _)| name == "MsgLaser")
+            .map(|(_, json)| json["angle"].as_f64().unwrap() as f32)
+            .collect();
+        assert_eq!(lasers, [255.0, 285.0]);
+
+        let [x, y] = velocity_sent();
+        assert!(x.abs() < 1e-5 && (y - 1.0).abs() < 1e-5, "{x}, {y}");
+    }
+
+    #[test]
+    fn wall_follow_correction_steers_away_when_too_close() {
+        assert!(wall_follow_correction(2.0, 3.0) < 0.0);
+        assert_eq!(
+            wall_follow_correction(0.0, 30.0),
+            -WALL_FOLLOW_MAX_CORRECTION
+        );
+
+        // The wall is on the right, so steering away turns left.
+        wall_at(2.0);
+        wall_follow_step(Side::Right, 3.0, 1.0).unwrap();
+        let [x, y] = velocity_sent();
+        assert!(x < 0.0 && y > 0.0, "{x}, {y}");
+    }
+
+    #[test]
+    fn wall_follow_correction_steers_towards_when_too_far() {
+        assert!(wall_follow_correction(4.0, 3.0) > 0.0);
+        assert_eq!(
+            wall_follow_correction(30.0, 3.0),
+            WALL_FOLLOW_MAX_CORRECTION
+        );
+
+        wall_at(4.0);
+        wall_follow_step(Side::Right, 3.0, 1.0).unwrap();
+        let [x, y] = velocity_sent();
+        assert!(x > 0.0 && y > 0.0, "{x}, {y}");
+    }
+
+    #[test]
+    fn nearest_corner_is_inset_from_the_walls() {
+        let arena = ArenaBounds::new(40.0, 25.0);
+        assert_eq!(nearest_corner(&arena, 4.0, [10.0, 3.0]), [36.0, 21.0]);
+        assert_eq!(nearest_corner(&arena, 4.0, [-10.0, 3.0]), [-36.0, 21.0]);
+        assert_eq!(nearest_corner(&arena, 4.0, [-1.0, -30.0]), [-36.0, -21.0]);
+        assert_eq!(nearest_corner(&arena, 4.0, [5.0, -0.5]), [36.0, -21.0]);
+        for corner in [[10.0, 3.0], [-1.0, -30.0]] {
+            let [x, y] = nearest_corner(&arena, 4.0, corner);
+            assert!(arena.in_bounds(x, y, 4.0));
+        }
+    }
+
+    #[test]
+    fn retreat_to_corner_rejects_non_positive_margin() {
+        stuck_at(0.0, 0.0);
+        let arena = ArenaBounds::new(40.0, 25.0);
+        assert!(matches!(
+            retreat_to_corner(&arena, 0.0, 1.0, 2.0),
+            Err(MessageError::InvalidArgument(_))
+        ));
+    }
+
+    #[test]
+    fn move_to_gives_up_when_stuck() {
+        stuck_at(0.0, 0.0);
+        assert_eq!(move_to(10.0, 0.0, 1.0, 1.0), Err(MessageError::Timeout));
+        let names = mock::sent_names();
+        let velocities = names.iter().filter(|name| *name == "MsgVelocity").count();
+        // One command per step, then the stop.
+        assert_eq!(velocities, DRIVE_MAX_STEPS as usize + 1);
+    }
+
+    #[test]
+    fn spiral_radius_increases_as_it_advances() {
+        let mut spiral = SpiralState::new(10.0, -5.0, 4.0, 30.0);
+        assert_eq!(spiral.radius(), 0.0);
+        let mut previous = spiral.radius();
+        for _ in 0..24 {
+            let [x, y] = spiral.advance();
+            let radius = spiral.radius();
+            assert!(radius > previous, "{radius} <= {previous}");
+            assert!(((x - 10.0).hypot(y + 5.0) - radius).abs() < 1e-3);
+            previous = radius;
+        }
+        // Two full turns, so two spacings out.
+        assert!((previous - 8.0).abs() < 1e-4);
+    }
+
+    #[test]
+    fn dodge_angle_is_perpendicular_to_the_threat() {
+        assert_eq!(dodge_angle(0.0, true), 90.0);
+        assert_eq!(dodge_angle(0.0, false), 270.0);
+        assert_eq!(dodge_angle(300.0, true), 30.0);
+        assert_eq!(dodge_angle(45.0, false), 315.0);
+    }
+
+    /// Answers as a robot that moves by the direction of each velocity command
+    /// with a non-zero speed, starting at the origin.
+    fn driving() {
+        let mut position = [0.0, 0.0];
+        mock::on_message(move |name, json| match name {
+            "MsgGPS" => MessageType::RGPS(msg::RMsgGPS {
+                x: position[0],
+                y: position[1],
+            }),
+            "MsgVelocity" => {
+                if json["speed"].as_f64().unwrap() > 0.0 {
+                    position[0] += json["x"].as_f64().unwrap() as f32;
+                    position[1] += json["y"].as_f64().unwrap() as f32;
+                }
+                MessageType::Empty(msg::MsgEmpty { value: 0 })
+            }
+            "MsgModuleStatusQuery" => {
+                MessageType::RModuleStatus(msg::RMsgModuleStatus { cooldown: 0.0 })
+            }
+            _ => MessageType::Empty(msg::MsgEmpty { value: 0 }),
+        });
+    }
+
+    #[test]
+    fn follow_path_visits_each_waypoint_and_stops_once() {
+        driving();
+        follow_path(&[[5.0, 0.0], [5.0, 5.0], [0.0, 5.0]], 1.0, 0.5).unwrap();
+
+        let velocities: Vec<_> = mock::sent()
+            .into_iter()
+            .filter(|(name, _)| name == "MsgVelocity")
+            .map(|(_, json)| {
+                let x = json["x"].as_f64().unwrap() as f32;
+                let y = json["y"].as_f64().unwrap() as f32;
+                let speed = json["speed"].as_f64().unwrap() as f32;
+                (x, y, speed)
+            })
+            .collect();
+        assert_eq!(
+            velocities,
+            [
+                (5.0, 0.0, 1.0),
+                (0.0, 5.0, 1.0),
+                (-5.0, 0.0, 1.0),
+                (0.0, 0.0, 0.0)
+            ]
+        );
+    }
+
+    #[test]
+    fn follow_path_does_nothing_for_an_empty_path() {
+        driving();
+        follow_path(&[], 1.0, 0.5).unwrap();
+        assert!(mock::sent().is_empty());
+    }
+}
@@ -0,0 +1,268 @@
+use rbot_messages::messages as msg;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+
+/// Rasterizes scan objects into an occupancy grid centered on the robot.
+///
+/// The grid covers the square from `-half_extent` to `half_extent` on both
+/// axes around the robot, split into cells of `cell_size` units. Cells are
+/// indexed as `grid[row][col]`, where the row grows with `y` and the column
+/// with `x`, see `cell_of`. A cell containing at least one object is 1, every
+/// other cell is 0. Objects outside the grid are ignored.
+///
+/// # Arguments
+///
+/// * `objects` - The scan objects to rasterize, e.g. from `rbot::modules::scan`.
+/// * `cell_size` - The width and height of a cell. Must be greater than 0.
+/// * `half_extent` - The distance from the robot to the edges of the grid.
+///
+/// # Returns
+///
+/// A square grid with `ceil(2 * half_extent / cell_size)` rows and columns,
+/// or an empty grid if `cell_size` or `half_extent` is not a finite number
+/// greater than 0.
+///
+/// # Examples
+///
+/// ```
+/// let scan_msg = rbot::modules::scan()?;
+/// let grid = rbot::nav::to_occupancy_grid(&scan_msg.objects, 2.0, 20.0);
+/// let blocked = grid.iter().flatten().filter(|cell| **cell != 0).count();
+/// ```
+pub fn to_occupancy_grid(
+    objects: &[msg::RMsgScanObject],
+    cell_size: f32,
+    half_extent: f32,
+) -> Vec<Vec<u8>> {
+    let size = grid_size(cell_size, half_extent);
+    let mut grid = vec![vec![0; size]; size];
+    for object in objects {
+        if let Some((row, col)) = cell_of(object.x, object.y, cell_size, half_extent) {
+            grid[row][col] = 1;
+        }
+    }
+    grid
+}
+
+/// The number of rows and columns of a grid, see `to_occupancy_grid`.
+///
+/// Without this check, a `cell_size` of 0 or NaN would saturate the cast to
+/// `usize::MAX` and the grid allocation would panic.
+fn grid_size(cell_size: f32, half_extent: f32) -> usize {
+    let valid = |v: f32| v.is_finite() && v > 0.0;
+    if !valid(cell_size) || !valid(half_extent) {
+        return 0;
+    }
+    (2.0 * half_extent / cell_size).ceil() as usize
+}
+
+/// Computes the cell containing the position (`x`, `y`) relative to the grid's center.
+///
+/// # Returns
+///
+/// The cell as `(row, col)`, or `None` if the position is outside the grid.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(rbot::nav::cell_of(0.5, -0.5, 1.0, 2.0), Some((1, 2)));
+/// assert_eq!(rbot::nav::cell_of(3.0, 0.0, 1.0, 2.0), None);
+/// ```
+pub fn cell_of(x: f32, y: f32, cell_size: f32, half_extent: f32) -> Option<(usize, usize)> {
+    let size = grid_size(cell_size, half_extent);
+    let index = |v: f32| {
+        let i = ((v + half_extent) / cell_size).floor();
+        (i >= 0.0 && (i as usize) < size).then_some(i as usize)
+    };
+    Some((index(y)?, index(x)?))
+}
+
+/// Computes the position of a cell's center relative to the grid's center.
+///
+/// This is the inverse of `cell_of`.
+///
+/// # Returns
+///
+/// The position `[x, y]` of the center of the cell `(row, col)`.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(rbot::nav::cell_center((1, 2), 1.0, 2.0), [0.5, -0.5]);
+/// ```
+pub fn cell_center((row, col): (usize, usize), cell_size: f32, half_extent: f32) -> [f32; 2] {
+    let center = |i: usize| (i as f32 + 0.5) * cell_size - half_extent;
+    [center(col), center(row)]
+}
+
+/// Finds the shortest path between two cells of an occupancy grid with A*.
+///
+/// The path moves between horizontally and vertically adjacent cells and
+/// avoids every occupied (non-zero) cell. The start cell itself may be
+/// occupied, since it is usually where the robot stands.
+///
+/// # Arguments
+///
+/// * `grid` - The occupancy grid, e.g. from `to_occupancy_grid`.
+/// * `start` - The cell `(row, col)` to start from.
+/// * `goal` - The cell `(row, col)` to reach.
+///
+/// # Returns
+///
+/// The cells of the path from `start` to `goal`, both included, or `None` if
+/// either is outside the grid, the goal is occupied or it cannot be reached.
+///
+/// # Examples
+///
+/// ```
+/// let grid = vec![
+///     vec![0, 1, 0],
+///     vec![0, 1, 0],
+///     vec![0, 0, 0],
+/// ];
+/// let path = rbot::nav::astar(&grid, (0, 0), (0, 2)).unwrap();
+/// assert_eq!(path.len(), 7);
+/// ```
+pub fn astar(
+    grid: &[Vec<u8>],
+    start: (usize, usize),
+    goal: (usize, usize),
+) -> Option<Vec<(usize, usize)>> {
+    let is_free = |(row, col): (usize, usize)| grid.get(row)?.get(col).map(|cell| *cell == 0);
+    is_free(start)?;
+    if !is_free(goal)? {
+        return None;
+    }
+
+    let heuristic = |(row, col): (usize, usize)| row.abs_diff(goal.0) + col.abs_diff(goal.1);
+    let mut open = BinaryHeap::from([Reverse((heuristic(start), start))]);
+    let mut costs = HashMap::from([(start, 0)]);
+    let mut came_from = HashMap::new();
+
+    while let Some(Reverse((_, cell))) = open.pop() {
+        if cell == goal {
+            let mut path = vec![goal];
+            while let Some(previous) = came_from.get(path.last()?) {
+                path.push(*previous);
+            }
+            path.reverse();
+            return Some(path);
+        }
+
+        let cost = costs[&cell] + 1;
+        let (row, col) = cell;
+        let neighbors = [
+            row.checked_sub(1).map(|row| (row, col)),
+            Some((row + 1, col)),
+            col.checked_sub(1).map(|col| (row, col)),
+            Some((row, col + 1)),
+        ];
+        for neighbor in neighbors.into_iter().flatten() {
+            if is_free(neighbor) != Some(true) {
+                continue;
+            }
+            if costs.get(&neighbor).is_some_and(|known| *known <= cost) {
+                continue;
+            }
+            costs.insert(neighbor, cost);
+            came_from.insert(neighbor, cell);
+            open.push(Reverse((cost + heuristic(neighbor), neighbor)));
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn object(x: f32, y: f32) -> msg::RMsgScanObject {
+        msg::RMsgScanObject {
+            x,
+            y,
+            tag: String::new(),
+            kind: String::new(),
+            buffs: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn occupancy_grid_marks_cells_with_objects() {
+        let objects = [object(0.5, -0.5), object(0.6, -0.9), object(-1.5, 1.5)];
+        let grid = to_occupancy_grid(&objects, 1.0, 2.0);
+        assert_eq!(
+            grid,
+            [[0, 0, 0, 0], [0, 0, 1, 0], [0, 0, 0, 0], [1, 0, 0, 0]]
+        );
+    }
+
+    #[test]
+    fn occupancy_grid_ignores_objects_outside() {
+        let objects = [object(2.0, 0.0), object(0.0, -2.5), object(-9.0, 9.0)];
+        let grid = to_occupancy_grid(&objects, 1.0, 2.0);
+        assert!(grid.iter().flatten().all(|cell| *cell == 0));
+        assert_eq!(to_occupancy_grid(&[], 3.0, 4.0).len(), 3);
+    }
+
+    #[test]
+    fn occupancy_grid_is_empty_for_invalid_sizes() {
+        let objects = [object(0.5, -0.5)];
+        for (cell_size, half_extent) in [
+            (0.0, 2.0),
+            (-1.0, 2.0),
+            (f32::NAN, 2.0),
+            (f32::INFINITY, 2.0),
+            (1.0, 0.0),
+            (1.0, -2.0),
+            (1.0, f32::NAN),
+            (1.0, f32::INFINITY),
+        ] {
+            assert!(to_occupancy_grid(&objects, cell_size, half_extent).is_empty());
+            assert_eq!(cell_of(0.5, -0.5, cell_size, half_extent), None);
+        }
+    }
+
+    #[test]
+    fn cell_center_inverts_cell_of() {
+        for row in 0..4 {
+            for col in 0..4 {
+                let [x, y] = cell_center((row, col), 1.0, 2.0);
+                assert_eq!(cell_of(x, y, 1.0, 2.0), Some((row, col)));
+            }
+        }
+        assert_eq!(cell_of(-2.0, -2.0, 1.0, 2.0), Some((0, 0)));
+        assert_eq!(cell_of(2.0, 0.0, 1.0, 2.0), None);
+    }
+
+    fn is_connected(path: &[(usize, usize)]) -> bool {
+        path.windows(2)
+            .all(|pair| pair[0].0.abs_diff(pair[1].0) + pair[0].1.abs_diff(pair[1].1) == 1)
+    }
+
+    #[test]
+    fn astar_finds_the_shortest_path_around_walls() {
+        let grid = vec![vec![0, 1, 0], vec![0, 1, 0], vec![0, 0, 0]];
+        let path = astar(&grid, (0, 0), (0, 2)).unwrap();
+        assert_eq!(path.len(), 7);
+        assert_eq!(path.first(), Some(&(0, 0)));
+        assert_eq!(path.last(), Some(&(0, 2)));
+        assert!(is_connected(&path));
+        assert!(path.iter().all(|(row, col)| grid[*row][*col] == 0));
+    }
+
+    #[test]
+    fn astar_allows_an_occupied_start() {
+        let grid = vec![vec![1, 0], vec![0, 0]];
+        assert_eq!(astar(&grid, (0, 0), (0, 1)), Some(vec![(0, 0), (0, 1)]));
+        assert_eq!(astar(&grid, (1, 1), (1, 1)), Some(vec![(1, 1)]));
+    }
+
+    #[test]
+    fn astar_fails_without_a_path() {
+        let grid = vec![vec![0, 1, 0], vec![0, 1, 0], vec![0, 1, 0]];
+        assert_eq!(astar(&grid, (0, 0), (0, 2)), None);
+        assert_eq!(astar(&grid, (0, 0), (0, 1)), None);
+        assert_eq!(astar(&grid, (0, 0), (5, 0)), None);
+        assert_eq!(astar(&grid, (3, 0), (0, 0)), None);
+    }
+}
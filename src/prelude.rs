@@ -0,0 +1,28 @@
+//! The most commonly used items of `rbot`, importable with a single line.
+//!
+//! The `print!` and `println!` macros are not included, because a glob import
+//! of them would be ambiguous with the standard library macros. Call them as
+//! `rbot::print!` and `rbot::println!` instead.
+//!
+//! # Examples
+//!
+//! ```
+//! use rbot::prelude::*;
+//!
+//! pub fn main() {
+//!     velocity(0.0, 1.0, 1.0);
+//!     await_module(Module::Radar);
+//!     let radar_msg = radar().expect("failed to get radar message");
+//!     let angle = xy_to_angle(radar_msg.x, radar_msg.y);
+//!     await_aim(0, angle, 0.5);
+//!     use_component(0, false);
+//!     rbot::println!("Fired at {angle}");
+//! }
+//! ```
+
+pub use crate::conversions::{angle_to_xy, xy_to_angle};
+pub use crate::core::{
+    await_aim, await_component, rotate, sleep, state, time, use_component, velocity,
+};
+pub use crate::errors::MessageError;
+pub use crate::modules::{await_module, radar, scan, Module};
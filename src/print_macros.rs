@@ -77,3 +77,31 @@ macro_rules! println {
         rbot::print(&format!("{}\n", format!($($arg)*)));
     };
 }
+
+/// A println macro that buffers the line instead of printing it immediately.
+///
+/// This macro appends the formatted line to a global buffer with
+/// `rbot::buffer_line`. The buffered lines are written to the game console in
+/// a single call by `rbot::flush_log`, which is much faster than `println!`
+/// for verbose logging in a tight loop. Unflushed lines are lost if the robot
+/// panics, unless the panic hook from `rbot::install_panic_hook` is installed.
+///
+/// # Examples
+///
+/// ```
+/// for id in 0..4 {
+///     buffered_println!("Component {}", id);
+/// }
+/// rbot::flush_log();
+/// ```
+#[macro_export]
+macro_rules! buffered_println {
+    // Match when there are no arguments
+    () => {
+        rbot::buffer_line("");
+    };
+    // Match when there is one or more arguments
+    ($($arg:tt)*) => {
+        rbot::buffer_line(&format!($($arg)*));
+    };
+}
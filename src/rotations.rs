@@ -1,3 +1,5 @@
+use core::f32::consts::PI;
+
 /// Transforms a global rotation angle to the local reference frame of a specified component.
 ///
 /// This function converts a rotation angle specified in the global coordinate system to the
@@ -51,6 +53,38 @@ pub fn transform_rotation_from_component(component_id: i32, angle: f32) -> f32 {
     angle + (90. * component_id as f32)
 }
 
+/// Transforms a global rotation angle to the local reference frame of a
+/// component, normalized to [0, 360).
+///
+/// This behaves like `transform_rotation_to_component`, which can return
+/// angles outside [0, 360), e.g. negative angles for higher component ids.
+///
+/// # Examples
+///
+/// ```
+/// let local_angle = rbot::rotations::transform_rotation_to_component_normalized(5, 45.0);
+/// assert_eq!(local_angle, 315.0);
+/// ```
+pub fn transform_rotation_to_component_normalized(component_id: i32, angle: f32) -> f32 {
+    normalize_angle(transform_rotation_to_component(component_id, angle))
+}
+
+/// Transforms a rotation angle from the local reference frame of a component
+/// to the global coordinate system, normalized to [0, 360).
+///
+/// This behaves like `transform_rotation_from_component`, which can return
+/// angles of 360 and more, e.g. 370 for component 4 at 10 degrees.
+///
+/// # Examples
+///
+/// ```
+/// let global_angle = rbot::rotations::transform_rotation_from_component_normalized(5, 45.0);
+/// assert_eq!(global_angle, 135.0);
+/// ```
+pub fn transform_rotation_from_component_normalized(component_id: i32, angle: f32) -> f32 {
+    normalize_angle(transform_rotation_from_component(component_id, angle))
+}
+
 /// Computes the angular distance between two angles.
 ///
 /// This function calculates the smallest angular difference (distance) between two given angles,
@@ -67,15 +101,336 @@ pub fn transform_rotation_from_component(component_id: i32, angle: f32) -> f32 {
 /// The returned value represents the smallest magnitude difference between the angles,
 /// ranging from 0 to 180 degrees.
 ///
+/// # Guarantees
+///
+/// * The result is always in the range [0, 180], with opposite angles at exactly 180.
+/// * The distance is symmetric: `angle_distance(a, b) == angle_distance(b, a)`
+///   holds exactly, not only up to rounding.
+/// * Negative angles and angles outside [0, 360) are handled, e.g. -350° and
+///   10° are the same direction.
+///
 /// # Examples
 ///
 /// ```
 /// let angle1 = 30.0;
 /// let angle2 = 350.0;
 /// let distance = rbot::rotations::angle_distance(angle1, angle2);
-/// rbot::print(&format!("Angular distance: {:.2} degrees", distance));
+/// assert_eq!(distance, 40.0);
+///
+/// assert_eq!(rbot::rotations::angle_distance(-350.0, 10.0), 0.0);
+/// assert_eq!(rbot::rotations::angle_distance(0.0, 180.0), 180.0);
+/// assert_eq!(rbot::rotations::angle_distance(720.0, -90.0), 90.0);
 /// ```
 pub fn angle_distance(angle: f32, other_angle: f32) -> f32 {
-    let angle_difference = angle - other_angle;
-    ((angle_difference + 180.0).rem_euclid(360.0) - 180.0).abs()
+    // Using the absolute difference keeps the result exactly symmetric.
+    let difference = (angle - other_angle).abs().rem_euclid(360.0);
+    difference.min(360.0 - difference)
+}
+
+/// Checks whether two angles are less than `slack` degrees apart.
+///
+/// This wraps `angle_distance`, so angles on either side of 0/360 degrees are
+/// compared correctly.
+///
+/// # Arguments
+///
+/// * `a` - The first angle in degrees.
+/// * `b` - The second angle in degrees.
+/// * `slack` - The allowed difference in degrees.
+///
+/// # Examples
+///
+/// ```
+/// assert!(rbot::rotations::angle_within(359.0, 1.0, 2.5));
+/// ```
+pub fn angle_within(a: f32, b: f32, slack: f32) -> bool {
+    angle_distance(a, b) < slack
+}
+
+/// Rotates a point counter-clockwise around the origin.
+///
+/// # Arguments
+///
+/// * `point` - The point `[x, y]` to rotate.
+/// * `angle` - The angle in degrees to rotate by.
+///
+/// # Returns
+///
+/// The rotated point `[x, y]`.
+///
+/// # Examples
+///
+/// ```
+/// let [x, y] = rbot::rotations::rotate_point([1.0, 0.0], 90.0);
+/// assert!(x.abs() < 1e-6 && (y - 1.0).abs() < 1e-6);
+/// ```
+pub fn rotate_point([x, y]: [f32; 2], angle: f32) -> [f32; 2] {
+    let rad = angle * PI / 180.0;
+    let (sin, cos) = (rad.sin(), rad.cos());
+    [x * cos - y * sin, x * sin + y * cos]
+}
+
+/// The direction of a rotation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TurnDirection {
+    /// Towards increasing angles.
+    CounterClockwise,
+    /// Towards decreasing angles.
+    Clockwise,
+}
+
+/// Computes the direction of the shortest rotation from one angle to another.
+///
+/// # Arguments
+///
+/// * `from` - The current angle in degrees.
+/// * `to` - The target angle in degrees.
+///
+/// # Returns
+///
+/// The direction of the shortest rotation, or `None` if the angles are equal.
+/// Opposite angles are reached clockwise.
+///
+/// # Examples
+///
+/// ```
+/// use rbot::rotations::{shortest_turn, TurnDirection};
+///
+/// assert_eq!(shortest_turn(350.0, 10.0), Some(TurnDirection::CounterClockwise));
+/// assert_eq!(shortest_turn(10.0, 350.0), Some(TurnDirection::Clockwise));
+/// ```
+pub fn shortest_turn(from: f32, to: f32) -> Option<TurnDirection> {
+    let difference = normalize_angle(to - from);
+    if difference == 0.0 {
+        None
+    } else if difference < 180.0 {
+        Some(TurnDirection::CounterClockwise)
+    } else {
+        Some(TurnDirection::Clockwise)
+    }
+}
+
+/// Computes the angular distance between two angles with `f64` precision.
+///
+/// This is the `f64` variant of `angle_distance`, for code that accumulates
+/// angles over a long time where `f32` rounding errors add up.
+///
+/// # Examples
+///
+/// ```
+/// let distance = rbot::rotations::angle_distance_f64(30.0, 350.0);
+/// ```
+pub fn angle_distance_f64(angle: f64, other_angle: f64) -> f64 {
+    let difference = (angle - other_angle).abs().rem_euclid(360.0);
+    difference.min(360.0 - difference)
+}
+
+/// Normalizes an angle to the range [0, 360) degrees.
+///
+/// Angles produced by the component transforms can end up negative or above
+/// 360 degrees. This function wraps them back into a single turn.
+///
+/// # Arguments
+///
+/// * `angle` - The angle in degrees to normalize.
+///
+/// # Returns
+///
+/// The equivalent angle in degrees within the range [0, 360).
+///
+/// # Examples
+///
+/// ```
+/// let angle = rbot::rotations::normalize_angle(-90.0);
+/// assert_eq!(angle, 270.0);
+/// ```
+pub fn normalize_angle(angle: f32) -> f32 {
+    let normalized = angle.rem_euclid(360.0);
+    // `rem_euclid` may round tiny negative inputs up to exactly 360.
+    if normalized >= 360.0 {
+        0.0
+    } else {
+        normalized
+    }
+}
+
+/// Snaps an angle to the nearest multiple of `step_deg`, normalized to [0, 360).
+///
+/// Quantizing the aim angle avoids chattering between two nearly equal
+/// targets, since small changes in the input don't change the result.
+///
+/// # Arguments
+///
+/// * `angle` - The angle in degrees to quantize.
+/// * `step_deg` - The grid size in degrees. Must be greater than 0.
+///
+/// # Returns
+///
+/// The multiple of `step_deg` closest to `angle`, in the range [0, 360).
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(rbot::rotations::quantize_angle(47.0, 5.0), 45.0);
+/// assert_eq!(rbot::rotations::quantize_angle(358.0, 5.0), 0.0);
+/// ```
+pub fn quantize_angle(angle: f32, step_deg: f32) -> f32 {
+    normalize_angle((angle / step_deg).round() * step_deg)
+}
+
+/// Precomputed rotation offsets for each component of a robot.
+///
+/// The offsets match `transform_rotation_to_component` and
+/// `transform_rotation_from_component`, but are computed once so aim code in
+/// tight loops only needs a table lookup. Component ids outside the table fall
+/// back to the formula.
+///
+/// # Examples
+///
+/// ```
+/// let rotations = rbot::rotations::RotationConfig::new(4);
+/// let body_angle = rotations.to_component(1, 45.0);
+/// rbot::rotate(body_angle)?;
+/// ```
+pub struct RotationConfig {
+    offsets: Vec<f32>,
+}
+
+impl RotationConfig {
+    /// Creates the offset table for a robot with `component_count` components.
+    pub fn new(component_count: usize) -> Self {
+        let offsets = (0..component_count)
+            .map(|id| transform_rotation_to_component(id as i32, 0.0))
+            .collect();
+        Self { offsets }
+    }
+
+    /// Transforms a global rotation angle to the local reference frame of a component.
+    ///
+    /// Equivalent to `transform_rotation_to_component`.
+    pub fn to_component(&self, component_id: i32, angle: f32) -> f32 {
+        angle + self.offset(component_id)
+    }
+
+    /// Transforms a rotation angle from the local reference frame of a component to
+    /// the global coordinate system.
+    ///
+    /// Equivalent to `transform_rotation_from_component`.
+    pub fn from_component(&self, component_id: i32, angle: f32) -> f32 {
+        angle - self.offset(component_id)
+    }
+
+    fn offset(&self, component_id: i32) -> f32 {
+        usize::try_from(component_id)
+            .ok()
+            .and_then(|id| self.offsets.get(id))
+            .copied()
+            .unwrap_or_else(|| transform_rotation_to_component(component_id, 0.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rotation_config_matches_the_transform_functions() {
+        for count in [4, 6] {
+            let config = RotationConfig::new(count as usize);
+            for id in 0..count {
+                for angle in [0.0, 45.0, -90.0, 270.0] {
+                    assert_eq!(
+                        config.to_component(id, angle),
+                        transform_rotation_to_component(id, angle)
+                    );
+                    assert_eq!(
+                        config.from_component(id, angle),
+                        transform_rotation_from_component(id, angle)
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn rotation_config_falls_back_outside_the_table() {
+        let config = RotationConfig::new(2);
+        for id in [-1, 2, 7] {
+            assert_eq!(
+                config.to_component(id, 30.0),
+                transform_rotation_to_component(id, 30.0)
+            );
+        }
+    }
+
+    #[test]
+    fn angle_distance_f64_wraps_around() {
+        assert_eq!(angle_distance_f64(30.0, 350.0), 40.0);
+        assert_eq!(angle_distance_f64(350.0, 30.0), 40.0);
+        assert_eq!(angle_distance_f64(-90.0, 270.0), 0.0);
+        assert_eq!(angle_distance_f64(0.0, 180.0), 180.0);
+    }
+
+    #[test]
+    fn quantize_angle_snaps_to_the_nearest_step() {
+        assert_eq!(quantize_angle(47.0, 5.0), 45.0);
+        assert_eq!(quantize_angle(48.0, 5.0), 50.0);
+        assert_eq!(quantize_angle(90.0, 45.0), 90.0);
+    }
+
+    #[test]
+    fn quantize_angle_normalizes() {
+        assert_eq!(quantize_angle(358.0, 5.0), 0.0);
+        assert_eq!(quantize_angle(-44.0, 45.0), 315.0);
+        assert_eq!(quantize_angle(721.0, 10.0), 0.0);
+    }
+
+    #[test]
+    fn angle_distance_is_symmetric() {
+        let angles = [0.0, 0.1, 30.0, 179.9, 180.0, 359.9, -45.0, -350.0, 725.5];
+        for a in angles {
+            for b in angles {
+                assert_eq!(angle_distance(a, b), angle_distance(b, a), "{a}, {b}");
+            }
+        }
+    }
+
+    #[test]
+    fn angle_distance_is_within_0_and_180() {
+        let mut angle = -1000.0;
+        while angle < 1000.0 {
+            let distance = angle_distance(angle, 37.0);
+            assert!((0.0..=180.0).contains(&distance), "{angle}: {distance}");
+            angle += 7.3;
+        }
+        assert_eq!(angle_distance(90.0, 270.0), 180.0);
+        assert_eq!(angle_distance(45.0, 45.0), 0.0);
+    }
+
+    #[test]
+    fn angle_distance_wraps_around() {
+        assert_eq!(angle_distance(30.0, 350.0), 40.0);
+        assert_eq!(angle_distance(359.0, 1.0), 2.0);
+        assert_eq!(angle_distance(720.0, 0.0), 0.0);
+    }
+
+    #[test]
+    fn angle_distance_handles_negative_angles() {
+        assert_eq!(angle_distance(-350.0, 10.0), 0.0);
+        assert_eq!(angle_distance(-90.0, 90.0), 180.0);
+        assert_eq!(angle_distance(-10.0, -20.0), 10.0);
+        assert_eq!(angle_distance(720.0, -90.0), 90.0);
+    }
+
+    #[test]
+    fn normalized_transforms_stay_within_0_and_360() {
+        for angle in [0.0, 10.0, 45.0, 90.0, 359.0, -30.0, 720.0] {
+            let local = transform_rotation_to_component_normalized(5, angle);
+            let global = transform_rotation_from_component_normalized(5, angle);
+            assert!((0.0..360.0).contains(&local), "{angle}: {local}");
+            assert!((0.0..360.0).contains(&global), "{angle}: {global}");
+        }
+        assert_eq!(transform_rotation_to_component_normalized(5, 45.0), 315.0);
+        assert_eq!(transform_rotation_from_component_normalized(5, 45.0), 135.0);
+    }
 }
@@ -0,0 +1,86 @@
+//! A typed key-value store that persists across ticks.
+//!
+//! Strategy code loses its local state between calls unless it is threaded
+//! through the main loop by hand. Helpers such as trackers and schedulers can
+//! keep their state here instead, under a key of their choice.
+//!
+//! The robot runs single-threaded in its WASM sandbox, so the store is a
+//! `thread_local` map. Values stored on one thread are not visible on another.
+//!
+//! # Examples
+//!
+//! ```
+//! let shots = rbot::store::get::<u32>("shots").unwrap_or(0);
+//! rbot::use_component(0, false)?;
+//! rbot::store::set("shots", shots + 1);
+//! ```
+
+use std::any::Any;
+use std::cell::RefCell;
+use std::collections::HashMap;
+
+thread_local! {
+    static STORE: RefCell<HashMap<&'static str, Box<dyn Any>>> = RefCell::new(HashMap::new());
+}
+
+/// Stores `value` under `key`, replacing any previous value of any type.
+pub fn set<T: 'static>(key: &'static str, value: T) {
+    STORE.with(|store| store.borrow_mut().insert(key, Box::new(value)));
+}
+
+/// Returns a copy of the value stored under `key`.
+///
+/// # Returns
+///
+/// The value, or `None` if nothing is stored under `key` or the stored value
+/// is not of type `T`.
+pub fn get<T: Clone + 'static>(key: &'static str) -> Option<T> {
+    STORE.with(|store| store.borrow().get(key)?.downcast_ref::<T>().cloned())
+}
+
+/// Removes the value stored under `key` and returns it.
+///
+/// # Returns
+///
+/// The value, or `None` if nothing is stored under `key` or the stored value
+/// is not of type `T`, in which case it is left in the store.
+pub fn remove<T: 'static>(key: &'static str) -> Option<T> {
+    STORE.with(|store| {
+        let mut store = store.borrow_mut();
+        if !store.get(key)?.is::<T>() {
+            return None;
+        }
+        let value = store.remove(key)?.downcast::<T>().ok()?;
+        Some(*value)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn get_returns_the_last_value_set() {
+        assert_eq!(get::<u32>("shots"), None);
+        set("shots", 1u32);
+        set("shots", 2u32);
+        assert_eq!(get::<u32>("shots"), Some(2));
+        assert_eq!(get::<u32>("shots"), Some(2));
+    }
+
+    #[test]
+    fn get_and_remove_check_the_type() {
+        set("target", String::from("Rifle"));
+        assert_eq!(get::<u32>("target"), None);
+        assert_eq!(remove::<u32>("target"), None);
+        assert_eq!(get::<String>("target").as_deref(), Some("Rifle"));
+    }
+
+    #[test]
+    fn remove_takes_the_value_out() {
+        set("angle", 90.0f32);
+        assert_eq!(remove::<f32>("angle"), Some(90.0));
+        assert_eq!(remove::<f32>("angle"), None);
+        assert_eq!(get::<f32>("angle"), None);
+    }
+}
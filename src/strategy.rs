@@ -0,0 +1,109 @@
+use crate::core;
+use crate::errors::MessageError;
+
+/// The logic of a robot, run by `run_bot`.
+///
+/// Implement `tick` with the code that would otherwise go into the body of the
+/// main loop, and keep the state needed between ticks in the struct itself.
+///
+/// # Examples
+///
+/// ```
+/// use rbot::errors::MessageError;
+/// use rbot::strategy::Strategy;
+///
+/// struct Turret;
+///
+/// impl Strategy for Turret {
+///     fn tick(&mut self) -> Result<(), MessageError> {
+///         let radar_msg = rbot::modules::radar()?;
+///         let angle = rbot::conversions::xy_to_angle(radar_msg.x, radar_msg.y);
+///         rbot::await_aim(0, angle, 1.0)?;
+///         rbot::use_component(0, false)
+///     }
+/// }
+///
+/// pub fn main() {
+///     rbot::strategy::run_bot(Turret);
+/// }
+/// ```
+pub trait Strategy {
+    /// Called once before the first tick.
+    fn on_start(&mut self) {}
+
+    /// Called repeatedly for as long as the robot runs.
+    fn tick(&mut self) -> Result<(), MessageError>;
+}
+
+/// Runs a strategy: calls `on_start` once and then `tick` in a loop.
+///
+/// Errors returned from `tick` are logged with `print` and the loop goes on,
+/// so a single failed call to the game server does not stop the robot. After
+/// an error, the next tick waits for the next action loop, so a persistent
+/// error does not flood the game server and the log. The function only
+/// returns once the connection to the game server is lost.
+///
+/// # Arguments
+///
+/// * `strategy` - The strategy to run.
+pub fn run_bot<S: Strategy>(mut strategy: S) {
+    strategy.on_start();
+    loop {
+        match strategy.tick() {
+            Ok(()) => {}
+            Err(e) if e.root() == &MessageError::Disconnected => return,
+            Err(e) => {
+                core::print(&format!("Error in tick: {e}\n"));
+                match core::await_action() {
+                    Err(e) if e.root() == &MessageError::Disconnected => return,
+                    // Still back off if the game server rejects the wait itself.
+                    Err(_) => core::sleep(core::poll_interval()),
+                    Ok(()) => {}
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hostfn::mock;
+    use rbot_messages::messages as msg;
+    use rbot_messages::MessageType;
+
+    /// Fails every tick with a bad command until `ticks` ticks have run.
+    struct Failing {
+        ticks: u32,
+    }
+
+    impl Strategy for Failing {
+        fn tick(&mut self) -> Result<(), MessageError> {
+            if self.ticks == 0 {
+                return Err(MessageError::Disconnected);
+            }
+            self.ticks -= 1;
+            core::use_component(0, false)
+        }
+    }
+
+    #[test]
+    fn run_bot_waits_for_the_action_loop_after_errors() {
+        mock::on_message(|name, _| match name {
+            "MsgUse" => MessageType::Error(msg::MsgError { error_code: 1 }),
+            _ => MessageType::Empty(msg::MsgEmpty { value: 0 }),
+        });
+        run_bot(Failing { ticks: 3 });
+        assert_eq!(
+            mock::sent_names(),
+            [
+                "MsgUse",
+                "MsgAwaitAction",
+                "MsgUse",
+                "MsgAwaitAction",
+                "MsgUse",
+                "MsgAwaitAction"
+            ]
+        );
+    }
+}
@@ -0,0 +1,530 @@
+use crate::constants::tag;
+use crate::conversions;
+use crate::rotations;
+use rbot_messages::messages as msg;
+use std::collections::VecDeque;
+
+/// Weight used for object kinds that are not listed in the weights.
+const DEFAULT_WEIGHT: f32 = 1.0;
+
+/// Distance from the robot to a scan object, whose position is relative to the robot.
+fn distance(object: &msg::RMsgScanObject) -> f32 {
+    object.x.hypot(object.y)
+}
+
+/// Sorts the components among scan objects by how threatening they are, most
+/// threatening first.
+///
+/// Only objects tagged `tag::COMPONENT` are targets; walls, bots and other
+/// objects are left out. Each component is scored by the weight of its `kind`
+/// divided by its distance to the robot, so a dangerous component further away
+/// can outrank a harmless one nearby. Kinds that are not listed in `weights`
+/// get a weight of 1.
+///
+/// # Arguments
+///
+/// * `objects` - The scan objects to prioritize, e.g. from `rbot::modules::scan`.
+/// * `weights` - Pairs of component kind and weight.
+///
+/// # Returns
+///
+/// References to the components sorted by descending score.
+///
+/// # Examples
+///
+/// ```
+/// use rbot::constants::kind;
+///
+/// let scan_msg = rbot::modules::scan()?;
+/// let weights = [(kind::RIFLE, 5.0), (kind::SHIELD, 0.2)];
+/// let targets = rbot::targeting::prioritize_targets(&scan_msg.objects, &weights);
+/// if let Some(target) = targets.first() {
+///     rbot::aim(0, rbot::conversions::xy_to_angle(target.x, target.y))?;
+/// }
+/// ```
+pub fn prioritize_targets<'a>(
+    objects: &'a [msg::RMsgScanObject],
+    weights: &[(&str, f32)],
+) -> Vec<&'a msg::RMsgScanObject> {
+    let score = |object: &msg::RMsgScanObject| {
+        let weight = weights
+            .iter()
+            .find(|(kind, _)| *kind == object.kind)
+            .map_or(DEFAULT_WEIGHT, |(_, weight)| *weight);
+        weight / distance(object).max(f32::EPSILON)
+    };
+
+    let mut targets: Vec<_> = objects
+        .iter()
+        .filter(|object| object.tag == tag::COMPONENT)
+        .collect();
+    targets.sort_by(|a, b| score(b).total_cmp(&score(a)));
+    targets
+}
+
+/// Computes how spread out a group of scan objects is.
+///
+/// The spread is the largest distance between any two of the objects. A small
+/// spread means the objects are tightly packed, which favors area weapons such
+/// as mines, while a large spread favors single-target weapons.
+///
+/// # Arguments
+///
+/// * `objects` - The scan objects to measure.
+///
+/// # Returns
+///
+/// The largest pairwise distance between the objects, or `None` if there are
+/// fewer than two objects.
+///
+/// # Examples
+///
+/// ```
+/// let scan_msg = rbot::modules::scan()?;
+/// if let Some(spread) = rbot::targeting::cluster_spread(&scan_msg.objects) {
+///     rbot::println!("Enemy components are spread over {spread:.1} units.");
+/// }
+/// ```
+pub fn cluster_spread(objects: &[msg::RMsgScanObject]) -> Option<f32> {
+    if objects.len() < 2 {
+        return None;
+    }
+    let objects: Vec<_> = objects.iter().collect();
+    Some(spread(&objects))
+}
+
+/// The largest distance between any two of the objects, or 0 for fewer than two.
+pub(crate) fn spread(objects: &[&msg::RMsgScanObject]) -> f32 {
+    objects
+        .iter()
+        .enumerate()
+        .flat_map(|(i, a)| {
+            objects[i + 1..]
+                .iter()
+                .map(move |b| (a.x - b.x).hypot(a.y - b.y))
+        })
+        .fold(0.0, f32::max)
+}
+
+/// Sorts scan objects in place by their angle around the robot.
+///
+/// Objects are ordered by increasing angle in [0, 360), measured
+/// counter-clockwise from the positive x-axis. Objects at the same angle keep
+/// their original order.
+///
+/// # Arguments
+///
+/// * `objects` - The scan objects to sort, e.g. from `rbot::modules::scan`.
+///
+/// # Examples
+///
+/// ```
+/// let mut scan_msg = rbot::modules::scan()?;
+/// rbot::targeting::sort_by_angle(&mut scan_msg.objects);
+/// ```
+pub fn sort_by_angle(objects: &mut [msg::RMsgScanObject]) {
+    objects.sort_by(|a, b| angle(a).total_cmp(&angle(b)));
+}
+
+/// Collects the scan objects within an angular arc around the robot.
+///
+/// An object is inside the arc if its angle is at most `half_width_deg`
+/// degrees away from `center_deg`. The comparison wraps around, so an arc
+/// centered on 0° also contains objects at e.g. 350°. This is useful to aim
+/// cone-shaped weapons at the direction covering the most objects.
+///
+/// # Arguments
+///
+/// * `objects` - The scan objects to filter, e.g. from `rbot::modules::scan`.
+/// * `center_deg` - The direction of the center of the arc in degrees.
+/// * `half_width_deg` - Half of the opening angle of the arc in degrees.
+///
+/// # Returns
+///
+/// References to the objects inside the arc, in their original order.
+///
+/// # Examples
+///
+/// ```
+/// let scan_msg = rbot::modules::scan()?;
+/// let ahead = rbot::targeting::in_arc(&scan_msg.objects, 90.0, 15.0);
+/// rbot::println!("{} objects ahead", ahead.len());
+/// ```
+pub fn in_arc(
+    objects: &[msg::RMsgScanObject],
+    center_deg: f32,
+    half_width_deg: f32,
+) -> Vec<&msg::RMsgScanObject> {
+    objects
+        .iter()
+        .filter(|object| rotations::angle_distance(angle(object), center_deg) <= half_width_deg)
+        .collect()
+}
+
+/// Angle of a scan object around the robot in [0, 360).
+fn angle(object: &msg::RMsgScanObject) -> f32 {
+    conversions::xy_to_angle_360(object.x, object.y)
+}
+
+/// Finds the direction of the widest empty arc between the detected objects.
+///
+/// The objects are ordered by their angle around the robot, and the largest
+/// angular gap between two neighbouring objects is found, including the gap
+/// that wraps around from the last object back to the first. The middle of
+/// that gap is the direction furthest from all objects, e.g. the safest
+/// direction to flee.
+///
+/// # Arguments
+///
+/// * `objects` - The scan objects to consider, e.g. from `rbot::modules::scan`.
+///
+/// # Returns
+///
+/// The angle in degrees in [0, 360) bisecting the largest gap, or `None` if
+/// there are no objects.
+///
+/// # Examples
+///
+/// ```
+/// let scan_msg = rbot::modules::scan()?;
+/// if let Some(angle) = rbot::targeting::largest_gap(&scan_msg.objects) {
+///     let [x, y] = rbot::conversions::angle_to_xy(angle);
+///     rbot::velocity(x, y, 1.0)?;
+/// }
+/// ```
+pub fn largest_gap(objects: &[msg::RMsgScanObject]) -> Option<f32> {
+    let mut angles: Vec<f32> = objects.iter().map(angle).collect();
+    angles.sort_by(f32::total_cmp);
+
+    let first = *angles.first()?;
+    let last = *angles.last()?;
+
+    // Start with the gap wrapping around from the last object to the first.
+    let mut gap_start = last;
+    let mut gap_size = first + 360.0 - last;
+    for pair in angles.windows(2) {
+        if pair[1] - pair[0] > gap_size {
+            gap_start = pair[0];
+            gap_size = pair[1] - pair[0];
+        }
+    }
+    Some(rotations::normalize_angle(gap_start + gap_size / 2.0))
+}
+
+/// A rolling history of contact positions, e.g. of the enemy found by the radar.
+///
+/// Single radar readings are noisy, and the heading of an evasive enemy
+/// computed from only two readings jumps around. `ContactHistory` keeps the
+/// last `capacity` samples and averages over all of them. Once full, each new
+/// sample replaces the oldest one.
+///
+/// Positions relative to the robot change when the robot moves, so push
+/// absolute positions (see `rbot::modules::relative_to_absolute`) while moving.
+///
+/// # Examples
+///
+/// ```
+/// let mut history = rbot::targeting::ContactHistory::new(8);
+/// loop {
+///     rbot::modules::await_module(rbot::modules::Module::Radar)?;
+///     let radar_msg = rbot::modules::radar()?;
+///     history.push(radar_msg.x, radar_msg.y, rbot::time()?);
+///     if let Some(heading) = history.estimated_heading() {
+///         rbot::println!("Enemy heading {heading:.0}°");
+///     }
+/// }
+/// ```
+pub struct ContactHistory {
+    capacity: usize,
+    samples: VecDeque<(f32, f32, f32)>,
+}
+
+impl ContactHistory {
+    /// Creates an empty history holding at most `capacity` samples.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            samples: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    /// Adds the position (`x`, `y`) observed at time `t`, dropping the oldest
+    /// sample if the history is full.
+    pub fn push(&mut self, x: f32, y: f32, t: f32) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.samples.len() == self.capacity {
+            self.samples.pop_front();
+        }
+        self.samples.push_back((x, y, t));
+    }
+
+    /// The number of samples in the history.
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    /// Whether the history has no samples.
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    /// The average position `[x, y]` of the samples, or `None` if there are none.
+    pub fn smoothed_position(&self) -> Option<[f32; 2]> {
+        if self.samples.is_empty() {
+            return None;
+        }
+        let n = self.samples.len() as f32;
+        let (sum_x, sum_y) = self
+            .samples
+            .iter()
+            .fold((0.0, 0.0), |(sx, sy), (x, y, _)| (sx + x, sy + y));
+        Some([sum_x / n, sum_y / n])
+    }
+
+    /// The velocity `[x, y]` in units per second that best fits the samples.
+    ///
+    /// The velocity is the least squares slope of the positions over time, so
+    /// every sample contributes and single noisy readings have little effect.
+    ///
+    /// # Returns
+    ///
+    /// The estimated velocity, or `None` if there are fewer than two samples
+    /// or they were all taken at the same time.
+    pub fn estimated_velocity(&self) -> Option<[f32; 2]> {
+        let [mean_x, mean_y] = self.smoothed_position()?;
+        let mean_t = self.samples.iter().map(|(_, _, t)| t).sum::<f32>() / self.len() as f32;
+
+        let (mut vx, mut vy, mut var_t) = (0.0, 0.0, 0.0);
+        for (x, y, t) in &self.samples {
+            let dt = t - mean_t;
+            vx += dt * (x - mean_x);
+            vy += dt * (y - mean_y);
+            var_t += dt * dt;
+        }
+        if var_t <= f32::EPSILON {
+            return None;
+        }
+        Some([vx / var_t, vy / var_t])
+    }
+
+    /// The estimated direction of travel in degrees in [0, 360).
+    ///
+    /// # Returns
+    ///
+    /// The angle of `estimated_velocity`, or `None` if the velocity is unknown
+    /// or zero.
+    pub fn estimated_heading(&self) -> Option<f32> {
+        let [vx, vy] = self.estimated_velocity()?;
+        let heading = conversions::try_xy_to_angle(vx, vy)?;
+        Some(rotations::normalize_angle(heading))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::constants::kind;
+
+    fn object(x: f32, y: f32, tag: &str, kind: &str) -> msg::RMsgScanObject {
+        msg::RMsgScanObject {
+            x,
+            y,
+            tag: tag.to_string(),
+            kind: kind.to_string(),
+            buffs: Vec::new(),
+        }
+    }
+
+    fn component(x: f32, y: f32, kind: &str) -> msg::RMsgScanObject {
+        object(x, y, tag::COMPONENT, kind)
+    }
+
+    #[test]
+    fn prioritize_targets_never_ranks_walls() {
+        let objects = [
+            object(0.5, 0.0, tag::WALL, ""),
+            component(20.0, 0.0, kind::SHIELD),
+            object(1.0, 0.0, tag::BOT, ""),
+        ];
+        let targets = prioritize_targets(&objects, &[(kind::SHIELD, 0.1)]);
+        assert_eq!(targets.len(), 1);
+        assert_eq!(targets[0].tag, tag::COMPONENT);
+    }
+
+    #[test]
+    fn prioritize_targets_weighs_kind_against_distance() {
+        let objects = [
+            component(2.0, 0.0, kind::SHIELD),
+            component(10.0, 0.0, kind::RIFLE),
+            component(0.0, 4.0, kind::HAMMER),
+        ];
+        let weights = [(kind::RIFLE, 5.0), (kind::SHIELD, 0.2)];
+        let kinds: Vec<_> = prioritize_targets(&objects, &weights)
+            .iter()
+            .map(|object| object.kind.as_str())
+            .collect();
+        assert_eq!(kinds, [kind::RIFLE, kind::HAMMER, kind::SHIELD]);
+    }
+
+    #[test]
+    fn cluster_spread_needs_two_objects() {
+        assert_eq!(cluster_spread(&[]), None);
+        assert_eq!(cluster_spread(&[component(1.0, 1.0, kind::RIFLE)]), None);
+    }
+
+    #[test]
+    fn cluster_spread_is_the_largest_pairwise_distance() {
+        let objects = [
+            component(0.0, 0.0, kind::RIFLE),
+            component(3.0, 4.0, kind::RIFLE),
+            component(1.0, 0.0, kind::RIFLE),
+        ];
+        assert_eq!(cluster_spread(&objects), Some(5.0));
+    }
+
+    #[test]
+    fn cluster_spread_of_collinear_objects_spans_the_ends() {
+        let objects = [
+            component(2.0, 0.0, kind::RIFLE),
+            component(-7.0, 0.0, kind::RIFLE),
+            component(5.0, 0.0, kind::RIFLE),
+            component(0.0, 0.0, kind::RIFLE),
+        ];
+        assert_eq!(cluster_spread(&objects), Some(12.0));
+    }
+
+    #[test]
+    fn cluster_spread_of_a_tight_cluster_is_small() {
+        let objects = [
+            component(10.0, 10.0, kind::RIFLE),
+            component(10.5, 10.0, kind::RIFLE),
+            component(10.0, 10.5, kind::RIFLE),
+            component(10.25, 10.25, kind::RIFLE),
+        ];
+        let spread = cluster_spread(&objects).unwrap();
+        assert!((spread - 0.5_f32.hypot(0.5)).abs() < 1e-6, "{spread}");
+    }
+
+    fn angles(objects: &[msg::RMsgScanObject]) -> Vec<f32> {
+        objects.iter().map(angle).collect()
+    }
+
+    #[test]
+    fn sort_by_angle_orders_counter_clockwise_from_the_x_axis() {
+        let mut objects = [
+            component(0.0, -1.0, kind::RIFLE),
+            component(-1.0, 0.0, kind::RIFLE),
+            component(1.0, 0.0, kind::RIFLE),
+            component(0.0, 1.0, kind::RIFLE),
+        ];
+        sort_by_angle(&mut objects);
+        assert_eq!(angles(&objects), [0.0, 90.0, 180.0, 270.0]);
+    }
+
+    #[test]
+    fn sort_by_angle_is_stable() {
+        let mut objects = [
+            component(2.0, 2.0, kind::SHIELD),
+            component(0.0, -1.0, kind::RIFLE),
+            component(1.0, 1.0, kind::HAMMER),
+        ];
+        sort_by_angle(&mut objects);
+        let kinds: Vec<_> = objects.iter().map(|object| object.kind.as_str()).collect();
+        assert_eq!(kinds, [kind::SHIELD, kind::HAMMER, kind::RIFLE]);
+    }
+
+    fn at_angle(angle: f32) -> msg::RMsgScanObject {
+        let [x, y] = conversions::angle_to_xy(angle);
+        component(x * 5.0, y * 5.0, kind::RIFLE)
+    }
+
+    fn assert_angle(actual: Option<f32>, expected: f32) {
+        let actual = actual.unwrap();
+        assert!(
+            rotations::angle_distance(actual, expected) < 1e-3,
+            "{actual} != {expected}"
+        );
+    }
+
+    #[test]
+    fn largest_gap_needs_an_object() {
+        assert_eq!(largest_gap(&[]), None);
+        assert_angle(largest_gap(&[at_angle(90.0)]), 270.0);
+    }
+
+    #[test]
+    fn largest_gap_bisects_the_widest_arc() {
+        assert_angle(largest_gap(&[at_angle(0.0), at_angle(90.0)]), 225.0);
+        let objects = [at_angle(200.0), at_angle(20.0), at_angle(100.0)];
+        assert_angle(largest_gap(&objects), 290.0);
+    }
+
+    #[test]
+    fn largest_gap_wraps_around() {
+        assert_angle(largest_gap(&[at_angle(10.0), at_angle(350.0)]), 180.0);
+        assert_angle(largest_gap(&[at_angle(170.0), at_angle(190.0)]), 0.0);
+    }
+
+    #[test]
+    fn contact_history_drops_the_oldest_sample() {
+        let mut history = ContactHistory::new(2);
+        assert!(history.is_empty());
+        assert_eq!(history.smoothed_position(), None);
+        history.push(0.0, 0.0, 0.0);
+        history.push(2.0, 4.0, 1.0);
+        history.push(4.0, 8.0, 2.0);
+        assert_eq!(history.len(), 2);
+        assert_eq!(history.smoothed_position(), Some([3.0, 6.0]));
+
+        let mut empty = ContactHistory::new(0);
+        empty.push(1.0, 1.0, 0.0);
+        assert!(empty.is_empty());
+    }
+
+    #[test]
+    fn contact_history_fits_the_velocity() {
+        let mut history = ContactHistory::new(8);
+        history.push(1.0, 1.0, 0.0);
+        assert_eq!(history.estimated_velocity(), None);
+        // Moving down at 2 units per second with alternating noise on x.
+        for t in 1..6 {
+            let noise = if t % 2 == 0 { 0.1 } else { -0.1 };
+            history.push(1.0 + noise, 1.0 - 2.0 * t as f32, t as f32);
+        }
+        let [vx, vy] = history.estimated_velocity().unwrap();
+        assert!(vx.abs() < 0.05 && (vy + 2.0).abs() < 1e-4, "{vx}, {vy}");
+        let heading = history.estimated_heading().unwrap();
+        assert!((heading - 270.0).abs() < 2.0, "{heading}");
+    }
+
+    #[test]
+    fn contact_history_needs_distinct_times() {
+        let mut history = ContactHistory::new(4);
+        history.push(0.0, 0.0, 1.0);
+        history.push(5.0, 0.0, 1.0);
+        assert_eq!(history.estimated_velocity(), None);
+        assert_eq!(history.estimated_heading(), None);
+    }
+
+    #[test]
+    fn in_arc_keeps_objects_within_the_half_width() {
+        let objects = [
+            at_angle(80.0),
+            at_angle(100.0),
+            at_angle(110.0),
+            at_angle(270.0),
+        ];
+        assert_eq!(in_arc(&objects, 90.0, 15.0).len(), 2);
+        assert_eq!(in_arc(&objects, 90.0, 20.0).len(), 3);
+        assert!(in_arc(&objects, 180.0, 45.0).is_empty());
+    }
+
+    #[test]
+    fn in_arc_wraps_around() {
+        let objects = [at_angle(350.0), at_angle(5.0), at_angle(30.0)];
+        assert_eq!(in_arc(&objects, 0.0, 15.0).len(), 2);
+        assert_eq!(in_arc(&objects, -10.0, 1.0).len(), 1);
+    }
+}